@@ -1,10 +1,16 @@
-use crossterm::event::{Event, KeyCode};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
 use std::io::Stdout;
-use crate::data::context::{AppContext, ContextInteraction};
+use crate::data::context::{AppContext, AppMode, ContextInteraction};
+use crate::data::settings::WordSettings;
+use crate::data::matrix_editor::MatrixEditor;
 use crate::control::{parsing, visualization};
 use crate::control::parsing::{InputType, ValueType};
 use crate::stack::item::StackItem;
+use crate::math::word::Word;
 use crate::stack::functions::route_function_call;
+use crate::stack::registry;
+use crate::stack::transaction::run_transaction;
+use crate::stack::variables::{evaluate_name, name_value, push_selected_variable, rename_selected_variable, store_selected_variable};
 
 
 // function that takes parsed inputs and routes them to functions in functions.rs
@@ -12,24 +18,83 @@ pub(crate) fn stack_mode_flow(parsed: parsing::ParsedInput, context: &mut AppCon
     match parsed.input_type {
         InputType::FunctionCall { name, args } => {
             if let Err(e) = route_function_call(name, args, context) {
-                // todo: handle error
+                context.report_error(e);
             }
         },
         InputType::Value(value_type) => {
-            match value_type {
-                ValueType::Number(num) => context.stack.push(StackItem::Number(num)),
-                ValueType::Array(arr) => context.stack.push(StackItem::Array(arr)),
+            let item = value_to_item(value_type, &context.word);
+            // pushes go through a transaction too so they can be undone
+            let _ = run_transaction(context, |context| {
+                context.stack.push(item);
+                Ok(())
+            });
+        },
+        InputType::Identifier(name) => {
+            if let Err(e) = evaluate_name(&name, context) {
+                context.report_error(e);
             }
         },
     }
 }
+// Turns a parsed literal into a stack item; words without a size take the current word settings
+pub(crate) fn value_to_item(value_type: ValueType, word: &WordSettings) -> StackItem {
+    match value_type {
+        ValueType::Number(num) => StackItem::Number(num),
+        ValueType::Array(arr) => StackItem::Array(arr),
+        ValueType::Complex(c) => StackItem::Complex(c),
+        ValueType::Integer(int) => StackItem::Integer(int),
+        ValueType::Rational(rational) => StackItem::from_exact(rational),
+        ValueType::Word(bits, Some((size, signed))) => StackItem::Word(Word::new(bits, size, signed)),
+        ValueType::Word(bits, None) => StackItem::Word(Word::new(bits, word.size, word.signed)),
+        ValueType::Quantity(quantity) => StackItem::Quantity(quantity),
+        ValueType::String(text) => StackItem::String(text),
+        ValueType::Date(date) => StackItem::Date(date),
+        ValueType::Duration(duration) => StackItem::Duration(duration),
+    }
+}
+
 pub(crate) fn program_mode_flow(parsed: parsing::ParsedInput, context: &mut AppContext) {}
-pub(crate) fn variables_mode_flow(parsed: parsing::ParsedInput, context: &mut AppContext) {}
-pub(crate) fn matrix_mode_flow(parsed: parsing::ParsedInput, context: &mut AppContext) {}
+// Works like the stack, except that commands on one variable default to the selected one
+pub(crate) fn variables_mode_flow(parsed: parsing::ParsedInput, context: &mut AppContext) {
+    let parsed = match parsed.input_type {
+        InputType::FunctionCall { name, mut args } => {
+            let selected = context.selected_variable_name();
+            match (registry::lookup(&name).map(|spec| spec.name), selected) {
+                (Some("rename"), Some(selected)) if args.len() == 1 => args.insert(0, selected),
+                (Some("rcl" | "purge" | "sto"), Some(selected)) if args.is_empty() => args.push(selected),
+                _ => {},
+            }
+            parsing::ParsedInput { input_type: InputType::FunctionCall { name, args } }
+        },
+        input_type => parsing::ParsedInput { input_type },
+    };
+    stack_mode_flow(parsed, context);
+}
+// With a matrix open, values go into the selected cell; commands still run as usual
+pub(crate) fn matrix_mode_flow(parsed: parsing::ParsedInput, context: &mut AppContext) {
+    if context.matrix_editor.is_none() {
+        return stack_mode_flow(parsed, context);
+    }
+    let item = match parsed.input_type {
+        InputType::Value(value_type) => Ok(value_to_item(value_type, &context.word)),
+        InputType::Identifier(name) => name_value(&name, context),
+        function_call => return stack_mode_flow(parsing::ParsedInput { input_type: function_call }, context),
+    };
+    match item.and_then(|item| item.as_number()) {
+        Ok(value) => {
+            if let Some(editor) = &mut context.matrix_editor {
+                editor.enter_value(value);
+            }
+        },
+        Err(e) => context.report_error(e),
+    }
+}
 
 pub(crate) fn process_event(event: Event, context: &mut AppContext, stdout: &mut Stdout) {
     match event {
         Event::Key(key_event) => {
+            // messages only live until the next keystroke
+            context.clear_status();
             match key_event.code {
                 KeyCode::Tab => {
                     // change mode
@@ -40,6 +105,19 @@ pub(crate) fn process_event(event: Event, context: &mut AppContext, stdout: &mut
                     // delete
                     context.input_buffer.pop();
                 },
+                KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right
+                    if key_event.modifiers.contains(KeyModifiers::SHIFT) && context.current_mode == AppMode::Matrix => {
+                    // shift+arrows grow a block selection in the matrix editor
+                    if let Some(editor) = &mut context.matrix_editor {
+                        let move_cursor = match key_event.code {
+                            KeyCode::Up => MatrixEditor::move_up,
+                            KeyCode::Down => MatrixEditor::move_down,
+                            KeyCode::Left => MatrixEditor::move_left,
+                            _ => MatrixEditor::move_right,
+                        };
+                        editor.extend_selection(move_cursor);
+                    }
+                },
                 KeyCode::Up => {
                     context.on_up_arrow();
                 },
@@ -52,7 +130,69 @@ pub(crate) fn process_event(event: Event, context: &mut AppContext, stdout: &mut
                 KeyCode::Right => {
                     context.on_right_arrow();
                 },
+                KeyCode::Char('z' | 'y') if key_event.modifiers.contains(KeyModifiers::CONTROL)
+                    && context.current_mode == AppMode::Matrix && context.matrix_editor.is_some() => {
+                    // with a matrix open, undo and redo work on its edits rather than the stack behind it
+                    if let Some(editor) = &mut context.matrix_editor {
+                        let (done, action) = match key_event.code {
+                            KeyCode::Char('z') => (editor.undo(), "undo"),
+                            _ => (editor.redo(), "redo"),
+                        };
+                        if !done {
+                            context.report_warning(format!("Nothing to {} in the open matrix", action));
+                        }
+                    }
+                },
+                KeyCode::Char('z') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    context.undo();
+                },
+                KeyCode::Char('y') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    context.redo();
+                },
+                KeyCode::Char('s') if key_event.modifiers.contains(KeyModifiers::CONTROL) && context.current_mode == AppMode::Matrix => {
+                    if let Err(e) = route_function_call("write".to_string(), Vec::new(), context) {
+                        context.report_error(e);
+                    }
+                },
+                KeyCode::Char('p') if key_event.modifiers.contains(KeyModifiers::CONTROL) && context.current_mode == AppMode::Matrix => {
+                    if let Err(e) = route_function_call("push".to_string(), Vec::new(), context) {
+                        context.report_error(e);
+                    }
+                },
+                KeyCode::Char('s') if key_event.modifiers.contains(KeyModifiers::CONTROL) && context.current_mode == AppMode::Variables => {
+                    let typed = std::mem::take(&mut context.input_buffer);
+                    if let Err(e) = store_selected_variable(&typed, context) {
+                        context.input_buffer = typed;
+                        context.report_error(e);
+                    }
+                },
+                KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) && context.current_mode == AppMode::Variables => {
+                    let typed = std::mem::take(&mut context.input_buffer);
+                    if let Err(e) = rename_selected_variable(&typed, context) {
+                        context.input_buffer = typed;
+                        context.report_error(e);
+                    }
+                },
+                KeyCode::Esc if context.list_view.is_none() && context.current_mode == AppMode::Matrix => {
+                    // discards edits that were not written or pushed
+                    context.matrix_editor = None;
+                },
+                KeyCode::Esc => {
+                    context.list_view = None;
+                },
+                KeyCode::Delete => {
+                    context.on_delete();
+                },
+                KeyCode::Enter if context.input_buffer.trim().is_empty()
+                    && context.current_mode == AppMode::Variables
+                    && context.list_view.is_none() => {
+                    // Enter on its own pushes the selected variable
+                    if let Err(e) = push_selected_variable(context) {
+                        context.report_error(e);
+                    }
+                },
                 KeyCode::Enter => {
+                    context.list_view = None;
                     // send buffer to be parsed
                     parsing::parse_input(context);
                     context.input_buffer.clear();
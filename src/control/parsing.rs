@@ -1,6 +1,14 @@
 use crate::control::flow::{matrix_mode_flow, program_mode_flow, stack_mode_flow, variables_mode_flow};
 use crate::data::context::{AppContext, AppMode};
 use crate::utils::misc::LoopControl;
+use crate::stack::item::StackItem;
+use crate::math::bigint::BigInt;
+use crate::math::complex::Complex;
+use crate::math::rational::Rational;
+use crate::math::word::{parse_word_literal, WordShape};
+use crate::math::units::Quantity;
+use crate::math::datetime::{Date, Duration};
+use crate::stack::transaction::{pop_operands, run_transaction};
 
 pub(crate) fn parse_quick_cmds(context: &mut AppContext) {
     match context.input_buffer.as_str() {
@@ -9,37 +17,56 @@ pub(crate) fn parse_quick_cmds(context: &mut AppContext) {
             context.input_buffer.clear()
         },
         "clear" => {
-            context.stack.clear();
-            context.input_buffer.clear()
-        },
-        "*" => {
-            let lhs = context.stack.pop().unwrap();
-            let rhs = context.stack.pop().unwrap();
-            match lhs*rhs {
-                Ok(result) => {context.stack.push(result)}
-                Err(e) => ()
-            }
-            context.input_buffer.clear()
-        },
-        "/" => {
-            context.input_buffer.clear()
-        },
-        "+" => {
-            context.input_buffer.clear()
-        },
-        "-" => {
-            context.input_buffer.clear()
-        },
-        "^" => {
+            // undoable, since this fires as soon as the word is typed
+            let _ = run_transaction(context, |context| {
+                context.stack.clear();
+                Ok(())
+            });
             context.input_buffer.clear()
         },
+        // operators are typed into the cell while editing a matrix
+        "*" | "/" | "+" | "^" if editing_matrix(context) => {},
+        "*" => binary_quick_cmd(context, |lhs, rhs| lhs * rhs),
+        "/" => binary_quick_cmd(context, |lhs, rhs| lhs / rhs),
+        "+" => binary_quick_cmd(context, |lhs, rhs| lhs + rhs),
+        // "-" may start a negative literal such as -5 or -3/7, so it only subtracts on Enter
+        "^" => binary_quick_cmd(context, |lhs, rhs| lhs.pow(rhs)),
         _ => {}
     }
 }
 
+fn editing_matrix(context: &AppContext) -> bool {
+    context.current_mode == AppMode::Matrix && context.matrix_editor.is_some()
+}
+
+// Applies op to the second and first stack levels (in that order), so that
+// "a b -" computes a - b. Operands are restored if the operation fails.
+fn binary_quick_cmd<F>(context: &mut AppContext, op: F)
+where
+    F: Fn(StackItem, StackItem) -> Result<StackItem, String>,
+{
+    context.input_buffer.clear();
+    let result = run_transaction(context, |context| {
+        let mut operands = pop_operands(&mut context.stack, 2)?;
+        let rhs = operands.pop().unwrap();
+        let lhs = operands.pop().unwrap();
+        context.stack.push(op(lhs, rhs)?);
+        Ok(())
+    });
+    if let Err(e) = result {
+        context.report_error(e);
+    }
+}
+
 pub(crate) fn parse_input(context: &mut AppContext) {
     // Parse input into usable data
     let buf: String = context.input_buffer.clone();
+    if buf.trim().is_empty() {
+        return;
+    }
+    if buf.trim() == "-" && !editing_matrix(context) {
+        return binary_quick_cmd(context, |lhs, rhs| lhs - rhs);
+    }
     match ParsedInput::create_from_buf(buf) {
         Ok(parsed) => {
             // Route parsed input into respective flow
@@ -51,7 +78,7 @@ pub(crate) fn parse_input(context: &mut AppContext) {
             }
         }
         Err(error) => {
-            // todo: handle error
+            context.report_error(error);
         }
     }
 }
@@ -62,11 +89,22 @@ pub(crate) enum InputType {
         args: Vec<String>,
     },
     Value(ValueType),
+    // a bare name, evaluated as a variable or constant
+    Identifier(String),
 }
 
 pub(crate) enum ValueType {
     Number(f64),
     Array(Vec<Vec<f64>>),
+    Complex(Complex),
+    Integer(BigInt),
+    Rational(Rational),
+    // raw bits, given the current word size when pushed unless the literal names one
+    Word(u64, Option<WordShape>),
+    Quantity(Quantity),
+    String(String),
+    Date(Date),
+    Duration(Duration),
 }
 
 pub(crate) struct ParsedInput {
@@ -91,6 +129,52 @@ impl ParsedInput {
             }
         }
 
+        // Quoted strings
+        if let Some(text) = parse_string_literal(buf.trim()) {
+            return Ok(ParsedInput {
+                input_type: InputType::Value(ValueType::String(text?)),
+            });
+        }
+
+        // Binary integer literals (0x.., 0o.., 0b.., #..)
+        if let Some(word) = parse_word_literal(buf.trim()) {
+            let (bits, shape) = word?;
+            return Ok(ParsedInput {
+                input_type: InputType::Value(ValueType::Word(bits, shape)),
+            });
+        }
+
+        // ISO dates (2024-03-15, 2024-03-15T10:30) and durations (HH:MM:SS)
+        if let Some(date) = Date::parse(buf.trim()) {
+            return Ok(ParsedInput {
+                input_type: InputType::Value(ValueType::Date(date?)),
+            });
+        }
+        if let Some(duration) = Duration::parse(buf.trim()) {
+            return Ok(ParsedInput {
+                input_type: InputType::Value(ValueType::Duration(duration?)),
+            });
+        }
+
+        // Whole numbers and fractions are kept exact
+        if let Some(int) = BigInt::parse(buf.trim()) {
+            return Ok(ParsedInput {
+                input_type: InputType::Value(ValueType::Integer(int)),
+            });
+        }
+        if let Some(rational) = Rational::parse(buf.trim()) {
+            return Ok(ParsedInput {
+                input_type: InputType::Value(ValueType::Rational(rational?)),
+            });
+        }
+
+        // Numbers with units (9.81_m/s^2)
+        if let Some(quantity) = Quantity::parse(buf.trim()) {
+            return Ok(ParsedInput {
+                input_type: InputType::Value(ValueType::Quantity(quantity?)),
+            });
+        }
+
         // Try to parse the input as a single number
         if let Ok(num) = buf.parse::<f64>() {
             return Ok(ParsedInput {
@@ -98,8 +182,24 @@ impl ParsedInput {
             });
         }
 
-        // Try to parse the input as an array of numbers
-        let rows: Vec<&str> = buf.split(';').collect();
+        // Try to parse the input as a complex number
+        if let Some(c) = parse_complex(buf.trim()) {
+            return Ok(ParsedInput {
+                input_type: InputType::Value(ValueType::Complex(c)),
+            });
+        }
+
+        if is_identifier(buf.trim()) {
+            return Ok(ParsedInput {
+                input_type: InputType::Identifier(buf.trim().to_string()),
+            });
+        }
+
+        // Try to parse the input as an array of numbers; brackets are optional
+        // but keep a one element array from reading back as a number
+        let body = buf.trim();
+        let body = body.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')).unwrap_or(body);
+        let rows: Vec<&str> = body.split(';').collect();
         let mut is_valid = true;
         let mut parsed_array = Vec::new();
 
@@ -117,6 +217,9 @@ impl ParsedInput {
         }
 
         if is_valid {
+            if parsed_array.iter().any(|row| row.len() != parsed_array[0].len()) {
+                return Err("Array rows must all have the same number of elements".to_string());
+            }
             return Ok(ParsedInput {
                 input_type: InputType::Value(ValueType::Array(parsed_array)),
             });
@@ -125,6 +228,122 @@ impl ParsedInput {
         }
 
         // If input is not a number, an array of numbers, or a valid function call, return an error
-        Err("Failed to parse input.".to_string())
+        Err(format!("Failed to parse input: {}", buf.trim()))
+    }
+}
+
+// Variable names: a letter or underscore, then letters, digits or underscores
+pub(crate) fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) if first.is_alphabetic() || first == '_' => chars.all(|ch| ch.is_alphanumeric() || ch == '_'),
+        _ => false,
+    }
+}
+
+// Text between double quotes, with \" \\ \n and \t escapes
+pub(crate) fn parse_string_literal(text: &str) -> Option<Result<String, String>> {
+    let body = text.strip_prefix('"')?;
+    let mut result = String::new();
+    let mut chars = body.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if chars.as_str().is_empty() => return Some(Ok(result)),
+            '"' => return Some(Err("Unexpected text after closing quote".to_string())),
+            '\\' => match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(escaped @ ('"' | '\\')) => result.push(escaped),
+                Some(other) => return Some(Err(format!("Unknown escape: \\{}", other))),
+                None => break,
+            },
+            other => result.push(other),
+        }
+    }
+    Some(Err("Missing closing quote".to_string()))
+}
+
+// Quotes and escapes text so it reads back as the same string
+pub(crate) fn format_string_literal(text: &str) -> String {
+    let mut literal = String::from("\"");
+    for ch in text.chars() {
+        match ch {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            other => literal.push(other),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+// Accepts "(re,im)" pairs and "a+bi" / "a-bi" / "bi" forms
+fn parse_complex(text: &str) -> Option<Complex> {
+    if let Some(inner) = text.strip_prefix('(').and_then(|rest| rest.strip_suffix(')')) {
+        let (re, im) = inner.split_once(',')?;
+        return Some(Complex::new(re.trim().parse().ok()?, im.trim().parse().ok()?));
+    }
+
+    let body = text.strip_suffix('i')?;
+    // a lone "i" is a name, not a number
+    if body.is_empty() {
+        return None;
+    }
+    // the imaginary part starts at the last sign that isn't leading or part of an exponent
+    let split = body.char_indices()
+        .rev()
+        .find(|&(index, ch)| {
+            (ch == '+' || ch == '-') && index > 0 && !body[..index].ends_with(['e', 'E'])
+        })
+        .map(|(index, _)| index);
+    let (re, im) = match split {
+        Some(index) => (body[..index].parse::<f64>().ok()?, &body[index..]),
+        None => (0.0, body),
+    };
+    let im = match im {
+        "+" | "" => 1.0,
+        "-" => -1.0,
+        _ => im.parse::<f64>().ok()?,
+    };
+    Some(Complex::new(re, im))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Feeds keys the way process_event does: quick commands run after each character
+    fn type_line(context: &mut AppContext, line: &str) {
+        for ch in line.chars() {
+            context.input_buffer.push(ch);
+            parse_quick_cmds(context);
+        }
+        parse_input(context);
+        context.input_buffer.clear();
+    }
+
+    #[test]
+    fn negative_literals_push_values() {
+        let mut context = AppContext::default();
+        type_line(&mut context, "-5");
+        type_line(&mut context, "-3/7");
+        type_line(&mut context, "-1.5e3");
+        assert_eq!(context.stack, vec![
+            StackItem::Integer(BigInt::from(-5i64)),
+            StackItem::from_exact(Rational::parse("-3/7").unwrap().unwrap()),
+            StackItem::Number(-1500.0),
+        ]);
+        assert!(context.status.is_none());
+    }
+
+    #[test]
+    fn a_lone_minus_subtracts_on_enter() {
+        let mut context = AppContext::default();
+        type_line(&mut context, "10");
+        type_line(&mut context, "4");
+        type_line(&mut context, "-");
+        assert_eq!(context.stack, vec![StackItem::Integer(BigInt::from(6i64))]);
     }
 }
@@ -3,7 +3,7 @@ use crossterm::execute;
 use crossterm::terminal::{Clear, ClearType};
 
 use crate::data::context::AppContext;
-use crate::ui::drawables::{BorderDrawer, Drawable, InputAreaUpdater, MainAreaUpdater};
+use crate::ui::drawables::{BorderDrawer, Drawable, InputAreaUpdater, MainAreaUpdater, StatusLineDrawer};
 use crate::ui::text_formatting::{print_formatted_at, TextFormat};
 
 pub fn update_graphics(stdout: &mut Stdout, context: &AppContext) {
@@ -11,6 +11,7 @@ pub fn update_graphics(stdout: &mut Stdout, context: &AppContext) {
 
     InputAreaUpdater::draw(stdout, context);
     MainAreaUpdater::draw(stdout, context);
+    StatusLineDrawer::draw(stdout, context);
     BorderDrawer::draw(stdout, context);
 
     // print title after to write over top border
@@ -1,9 +1,16 @@
 // src/data/context
 
+use std::collections::BTreeMap;
+
 use crate::stack::functions::route_function_call;
 use crate::utils::misc::LoopControl;
 use crate::utils::terminal::TerminalSize;
 use crate::stack::item::StackItem;
+use crate::stack::history::{Snapshot, StackHistory, HISTORY_LIMIT};
+use crate::data::status::{MessageKind, StatusMessage};
+use crate::data::list_view::ListView;
+use crate::data::matrix_editor::MatrixEditor;
+use crate::data::settings::{AngleMode, DisplaySettings, WordSettings};
 
 pub struct AppContext {
     pub input_buffer: String,
@@ -11,6 +18,18 @@ pub struct AppContext {
     pub current_mode: AppMode,
     pub should_quit: LoopControl,
     pub stack: Vec<StackItem>,
+    pub status: Option<StatusMessage>,
+    pub history: StackHistory,
+    pub list_view: Option<ListView>,
+    pub display: DisplaySettings,
+    pub word: WordSettings,
+    pub angle: AngleMode,
+    // named values, kept sorted for listing
+    pub variables: BTreeMap<String, StackItem>,
+    // index of the highlighted variable in the variables view
+    pub selected_variable: usize,
+    // the array open in matrix mode, if any
+    pub matrix_editor: Option<MatrixEditor>,
 }
 
 impl Default for AppContext {
@@ -20,16 +39,95 @@ impl Default for AppContext {
             terminal_size: TerminalSize::new(),
             current_mode: AppMode::Stack,
             should_quit: LoopControl::Continue,
-            stack: vec![
-                StackItem::Number(3.14),
-                StackItem::Array(vec![vec![1.0, 2.0, 3.0]]), // Represents a 1D array
-                StackItem::Array(vec![vec![4.0, 5.0, 6.0], vec![7.0, 8.0, 9.0]]), // Represents a 2D array
-            ],
-            //stack: Vec::new(),
+            stack: Vec::new(),
+            status: None,
+            history: StackHistory::new(HISTORY_LIMIT),
+            list_view: None,
+            display: DisplaySettings::default(),
+            word: WordSettings::default(),
+            angle: AngleMode::default(),
+            variables: BTreeMap::new(),
+            selected_variable: 0,
+            matrix_editor: None,
         }
     }
 }
 
+impl AppContext {
+    pub(crate) fn report(&mut self, kind: MessageKind, text: impl Into<String>) {
+        self.status = Some(StatusMessage::new(kind, text));
+    }
+
+    pub(crate) fn report_error(&mut self, text: impl Into<String>) {
+        self.report(MessageKind::Error, text);
+    }
+
+    pub(crate) fn report_warning(&mut self, text: impl Into<String>) {
+        self.report(MessageKind::Warning, text);
+    }
+
+    pub(crate) fn report_info(&mut self, text: impl Into<String>) {
+        self.report(MessageKind::Info, text);
+    }
+
+    pub(crate) fn clear_status(&mut self) {
+        self.status = None;
+    }
+
+    pub(crate) fn undo(&mut self) {
+        let mut state = self.snapshot();
+        if self.history.undo(&mut state) {
+            self.restore(state);
+        } else {
+            self.report_warning("Nothing to undo");
+        }
+    }
+
+    pub(crate) fn selected_variable_name(&self) -> Option<String> {
+        self.variables.keys().nth(self.selected_variable).cloned()
+    }
+
+    // Keeps the selection on an existing variable after the list changes
+    pub(crate) fn clamp_variable_selection(&mut self) {
+        self.selected_variable = self.selected_variable.min(self.variables.len().saturating_sub(1));
+    }
+
+    pub(crate) fn redo(&mut self) {
+        let mut state = self.snapshot();
+        if self.history.redo(&mut state) {
+            self.restore(state);
+        } else {
+            self.report_warning("Nothing to redo");
+        }
+    }
+
+    // The undoable part of the context
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            stack: self.stack.clone(),
+            variables: self.variables.clone(),
+            display: self.display,
+            word: self.word,
+            angle: self.angle,
+        }
+    }
+
+    pub(crate) fn restore(&mut self, snapshot: Snapshot) {
+        self.stack = snapshot.stack;
+        self.variables = snapshot.variables;
+        self.clamp_variable_selection();
+        self.display = snapshot.display;
+        self.word = snapshot.word;
+        self.angle = snapshot.angle;
+    }
+
+    // True if nothing undoable changed since the snapshot was taken
+    pub(crate) fn matches(&self, snapshot: &Snapshot) -> bool {
+        self.stack == snapshot.stack && self.variables == snapshot.variables && self.display == snapshot.display
+            && self.word == snapshot.word && self.angle == snapshot.angle
+    }
+}
+
 #[derive(PartialEq)]
 pub(crate) enum AppMode {
     Stack,
@@ -54,24 +152,53 @@ pub(crate) trait ContextInteraction {
     fn on_down_arrow(&mut self);
     fn on_left_arrow(&mut self);
     fn on_right_arrow(&mut self);
+    fn on_delete(&mut self);
 }
 
 impl ContextInteraction for AppContext {
     fn on_up_arrow(&mut self) {
+        if let Some(list_view) = &mut self.list_view {
+            list_view.scroll_up();
+            return;
+        }
         match self.current_mode {
             AppMode::Stack => { /* Stack-specific logic */ },
             AppMode::Program => { /* Program-specific logic */ },
-            AppMode::Matrix => { /* Matrix-specific logic */ },
-            AppMode::Variables => { /* Variables-specific logic */ },
+            AppMode::Matrix => {
+                if let Some(editor) = &mut self.matrix_editor {
+                    editor.move_up();
+                }
+            },
+            AppMode::Variables => {
+                self.selected_variable = self.selected_variable.saturating_sub(1);
+            },
         }
     }
 
     fn on_down_arrow(&mut self) {
-        // Implement the logic for down arrow
+        if let Some(list_view) = &mut self.list_view {
+            list_view.scroll_down();
+            return;
+        }
+        match self.current_mode {
+            AppMode::Matrix => {
+                if let Some(editor) = &mut self.matrix_editor {
+                    editor.move_down();
+                }
+            },
+            AppMode::Variables if self.selected_variable + 1 < self.variables.len() => {
+                self.selected_variable += 1;
+            },
+            _ => {},
+        }
     }
 
     fn on_left_arrow(&mut self) {
-        // Implement the logic for left arrow
+        if self.current_mode == AppMode::Matrix {
+            if let Some(editor) = &mut self.matrix_editor {
+                editor.move_left();
+            }
+        }
     }
 
     fn on_right_arrow(&mut self) {
@@ -79,12 +206,26 @@ impl ContextInteraction for AppContext {
             AppMode::Stack => {
                 // Use route_function_call to call the dup function
                 if let Err(e) = route_function_call("dup".to_string(), Vec::new(), self) {
-                    // todo: handle error
+                    self.report_error(e);
                 }
             },
             AppMode::Program => { /* Program-specific logic */ },
-            AppMode::Matrix => { /* Matrix-specific logic */ },
+            AppMode::Matrix => {
+                if let Some(editor) = &mut self.matrix_editor {
+                    editor.move_right();
+                }
+            },
             AppMode::Variables => { /* Variables-specific logic */ },
         }
     }
+
+    fn on_delete(&mut self) {
+        if self.current_mode == AppMode::Variables {
+            if let Some(name) = self.selected_variable_name() {
+                if let Err(e) = route_function_call("purge".to_string(), vec![name], self) {
+                    self.report_error(e);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,25 @@
+// src/data/list_view
+
+// A read-only, scrollable list shown over the main area (function help, catalogs).
+// Up/Down scroll it and Esc closes it.
+pub(crate) struct ListView {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub scroll: usize,
+}
+
+impl ListView {
+    pub(crate) fn new(title: impl Into<String>, lines: Vec<String>) -> ListView {
+        ListView { title: title.into(), lines, scroll: 0 }
+    }
+
+    pub(crate) fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub(crate) fn scroll_down(&mut self) {
+        if self.scroll + 1 < self.lines.len() {
+            self.scroll += 1;
+        }
+    }
+}
@@ -0,0 +1,270 @@
+// src/data/matrix_editor
+
+use crate::math::linalg::{shape, Matrix};
+use crate::stack::history::{History, HISTORY_LIMIT};
+
+// An array being edited cell by cell in matrix mode. The edits stay here until
+// they are written back over the original or pushed as a new array.
+pub(crate) struct MatrixEditor {
+    pub data: Matrix,
+    pub row: usize,
+    pub col: usize,
+    // where the array was opened from, if it came from the stack
+    pub source: Option<Source>,
+    // the other corner of a shift+arrow selection; the cursor is the first
+    pub anchor: Option<(usize, usize)>,
+    pub clipboard: Option<Matrix>,
+    // earlier contents of the grid, so ctrl-z works on the edits while it is open
+    pub history: History<Matrix>,
+}
+
+// The stack position (from the bottom) of the opened array and its contents at
+// the time, so writing back can tell whether that slot still holds the same array
+pub(crate) struct Source {
+    pub index: usize,
+    pub original: Matrix,
+}
+
+// Inclusive cell range, top-left to bottom-right
+#[derive(Clone, Copy)]
+pub(crate) struct Block {
+    pub top: usize,
+    pub left: usize,
+    pub bottom: usize,
+    pub right: usize,
+}
+
+impl Block {
+    pub(crate) fn contains(&self, row: usize, col: usize) -> bool {
+        (self.top..=self.bottom).contains(&row) && (self.left..=self.right).contains(&col)
+    }
+}
+
+impl MatrixEditor {
+    pub(crate) fn new(data: Matrix, source: Option<usize>) -> MatrixEditor {
+        let source = source.map(|index| Source { index, original: data.clone() });
+        MatrixEditor { data, row: 0, col: 0, source, anchor: None, clipboard: None, history: History::new(HISTORY_LIMIT) }
+    }
+
+    // Called by every edit just before it changes the grid
+    fn checkpoint(&mut self) {
+        self.history.record(self.data.clone());
+    }
+
+    // Returns false if there is no edit to undo
+    pub(crate) fn undo(&mut self) -> bool {
+        let undone = self.history.undo(&mut self.data);
+        if undone {
+            self.clamp_cursor();
+        }
+        undone
+    }
+
+    // Returns false if there is no undone edit to redo
+    pub(crate) fn redo(&mut self) -> bool {
+        let redone = self.history.redo(&mut self.data);
+        if redone {
+            self.clamp_cursor();
+        }
+        redone
+    }
+
+    pub(crate) fn shape(&self) -> (usize, usize) {
+        shape(&self.data)
+    }
+
+    // No cells at all, so there is nothing under the cursor to act on
+    pub(crate) fn is_empty(&self) -> bool {
+        let (rows, cols) = self.shape();
+        rows == 0 || cols == 0
+    }
+
+    pub(crate) fn move_up(&mut self) {
+        self.anchor = None;
+        self.row = self.row.saturating_sub(1);
+    }
+
+    pub(crate) fn move_down(&mut self) {
+        self.anchor = None;
+        if self.row + 1 < self.shape().0 {
+            self.row += 1;
+        }
+    }
+
+    pub(crate) fn move_left(&mut self) {
+        self.anchor = None;
+        self.col = self.col.saturating_sub(1);
+    }
+
+    pub(crate) fn move_right(&mut self) {
+        self.anchor = None;
+        if self.col + 1 < self.shape().1 {
+            self.col += 1;
+        }
+    }
+
+    // Moves the cursor while keeping the selection's other corner in place
+    pub(crate) fn extend_selection(&mut self, move_cursor: fn(&mut MatrixEditor)) {
+        let anchor = self.anchor.unwrap_or((self.row, self.col));
+        move_cursor(self);
+        self.anchor = Some(anchor);
+    }
+
+    // The selected block, or just the cursor cell
+    pub(crate) fn selection(&self) -> Block {
+        let (row, col) = self.anchor.unwrap_or((self.row, self.col));
+        Block {
+            top: row.min(self.row),
+            left: col.min(self.col),
+            bottom: row.max(self.row),
+            right: col.max(self.col),
+        }
+    }
+
+    // Sets the current cell and moves on to the next one, row by row
+    pub(crate) fn enter_value(&mut self, value: f64) {
+        if self.is_empty() {
+            return;
+        }
+        let (rows, cols) = self.shape();
+        self.checkpoint();
+        self.anchor = None;
+        self.data[self.row][self.col] = value;
+        if self.col + 1 < cols {
+            self.col += 1;
+        } else if self.row + 1 < rows {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    // A blank row above the cursor
+    pub(crate) fn insert_row(&mut self) {
+        let cols = self.shape().1;
+        self.checkpoint();
+        self.data.insert(self.row, vec![0.0; cols]);
+        self.anchor = None;
+    }
+
+    pub(crate) fn delete_row(&mut self) -> Result<(), String> {
+        if self.shape().0 <= 1 {
+            return Err("A matrix needs at least one row".to_string());
+        }
+        self.checkpoint();
+        self.data.remove(self.row);
+        self.clamp_cursor();
+        Ok(())
+    }
+
+    // A blank column left of the cursor
+    pub(crate) fn insert_col(&mut self) {
+        self.checkpoint();
+        for row in &mut self.data {
+            row.insert(self.col, 0.0);
+        }
+        self.anchor = None;
+    }
+
+    pub(crate) fn delete_col(&mut self) -> Result<(), String> {
+        if self.shape().1 <= 1 {
+            return Err("A matrix needs at least one column".to_string());
+        }
+        self.checkpoint();
+        for row in &mut self.data {
+            row.remove(self.col);
+        }
+        self.clamp_cursor();
+        Ok(())
+    }
+
+    // Keeps the top-left values; new cells are zero
+    pub(crate) fn resize(&mut self, rows: usize, cols: usize) {
+        self.checkpoint();
+        self.data.resize(rows, Vec::new());
+        for row in &mut self.data {
+            row.resize(cols, 0.0);
+        }
+        self.clamp_cursor();
+    }
+
+    pub(crate) fn fill(&mut self, value: f64) {
+        if self.is_empty() {
+            return;
+        }
+        let block = self.selection();
+        self.checkpoint();
+        for row in &mut self.data[block.top..=block.bottom] {
+            row[block.left..=block.right].fill(value);
+        }
+    }
+
+    pub(crate) fn copy(&mut self) {
+        if self.is_empty() {
+            return;
+        }
+        let block = self.selection();
+        self.clipboard = Some(
+            self.data[block.top..=block.bottom].iter()
+                .map(|row| row[block.left..=block.right].to_vec())
+                .collect(),
+        );
+    }
+
+    // Pastes with the top-left at the cursor; whatever falls outside is dropped
+    pub(crate) fn paste(&mut self) -> Result<(), String> {
+        if self.clipboard.is_none() {
+            return Err("Nothing copied".to_string());
+        }
+        self.checkpoint();
+        let block = self.clipboard.as_ref().unwrap();
+        let (rows, cols) = self.shape();
+        for (i, values) in block.iter().enumerate().take(rows.saturating_sub(self.row)) {
+            for (j, value) in values.iter().enumerate().take(cols.saturating_sub(self.col)) {
+                self.data[self.row + i][self.col + j] = *value;
+            }
+        }
+        self.anchor = None;
+        Ok(())
+    }
+
+    fn clamp_cursor(&mut self) {
+        let (rows, cols) = self.shape();
+        self.row = self.row.min(rows.saturating_sub(1));
+        self.col = self.col.min(cols.saturating_sub(1));
+        self.anchor = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_undo_and_redo() {
+        let mut editor = MatrixEditor::new(vec![vec![1.0, 2.0], vec![3.0, 4.0]], None);
+        let original = editor.data.clone();
+        editor.enter_value(9.0);
+        editor.insert_row();
+        editor.delete_col().unwrap();
+        editor.fill(5.0);
+        editor.copy();
+        editor.paste().unwrap();
+        editor.resize(4, 4);
+        let edited = editor.data.clone();
+
+        while editor.undo() {}
+        assert_eq!(editor.data, original);
+        while editor.redo() {}
+        assert_eq!(editor.data, edited);
+        assert!(editor.row < 4 && editor.col < 4);
+    }
+
+    #[test]
+    fn refused_edits_leave_nothing_to_undo() {
+        let mut editor = MatrixEditor::new(vec![vec![1.0]], None);
+        assert!(editor.delete_row().is_err());
+        assert!(editor.delete_col().is_err());
+        assert!(editor.paste().is_err());
+        assert!(!editor.undo());
+    }
+}
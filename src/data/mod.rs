@@ -1 +1,5 @@
-pub(crate) mod context;
\ No newline at end of file
+pub(crate) mod context;
+pub(crate) mod status;
+pub(crate) mod list_view;
+pub(crate) mod settings;
+pub(crate) mod matrix_editor;pub(crate) mod session;
@@ -0,0 +1,239 @@
+// src/data/session
+
+// The stack, variables and modes are kept between runs in a plain text file:
+//
+//      blang-session 1
+//      mode stack
+//      angle rad
+//      format fix 4
+//      base hex
+//      word 64 unsigned
+//      item 3.14
+//      var x "hello"
+//
+// Items are written as input literals (StackItem::to_literal) and read back through
+// the parser, bottom of the stack first. Unknown or unreadable lines are skipped so a
+// damaged file still restores what it can.
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::control::flow::value_to_item;
+use crate::control::parsing::{is_identifier, InputType, ParsedInput};
+use crate::data::context::{AppContext, AppMode};
+use crate::data::settings::{AngleMode, NumberFormat};
+use crate::math::word::{Base, WORD_SIZES};
+use crate::stack::item::StackItem;
+
+const HEADER: &str = "blang-session";
+const VERSION: u32 = 1;
+
+// How often the loop writes the session while running, so a crash loses little
+pub(crate) const AUTOSAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+// $XDG_DATA_HOME/blang/session, falling back to ~/.local/share/blang/session
+pub(crate) fn session_path() -> Option<PathBuf> {
+    let data_dir = match std::env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME").filter(|dir| !dir.is_empty())?).join(".local/share"),
+    };
+    Some(data_dir.join("blang").join("session"))
+}
+
+// Writes to a temporary file first and renames it over the old session, so an
+// interrupted save never leaves a half written file behind
+pub(crate) fn save(context: &AppContext) -> Result<(), String> {
+    let path = session_path().ok_or("No home directory to save the session in")?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    }
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, render(context)).map_err(|e| format!("Could not write {}: {}", temporary.display(), e))?;
+    fs::rename(&temporary, &path).map_err(|e| format!("Could not save {}: {}", path.display(), e))
+}
+
+// Restores the last session, if there is one, returning a warning if some lines
+// had to be skipped. A missing file is not an error. An Err means the file could not
+// be read or is not a session this version understands; the caller must not save
+// over it then.
+pub(crate) fn load(context: &mut AppContext) -> Result<Option<String>, String> {
+    let Some(path) = session_path() else {
+        return Ok(None);
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Could not read {}: {}", path.display(), e)),
+    };
+    let skipped = restore(&text, context).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if skipped > 0 {
+        return Ok(Some(format!("Session restored; skipped {} unreadable line(s)", skipped)));
+    }
+    Ok(None)
+}
+
+fn render(context: &AppContext) -> String {
+    let mut lines = vec![
+        format!("{} {}", HEADER, VERSION),
+        format!("mode {}", mode_name(&context.current_mode)),
+        format!("angle {}", context.angle.label().to_lowercase()),
+        format!("format {}", context.display.format.label().to_lowercase()),
+        format!("base {}", base_name(context.display.base)),
+        format!("word {} {}", context.word.size, if context.word.signed { "signed" } else { "unsigned" }),
+    ];
+    lines.extend(context.stack.iter().map(|item| format!("item {}", item.to_literal())));
+    lines.extend(context.variables.iter().map(|(name, item)| format!("var {} {}", name, item.to_literal())));
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+// Applies every readable line to the context and returns how many were skipped
+fn restore(text: &str, context: &mut AppContext) -> Result<usize, String> {
+    let mut lines = text.lines();
+    let version = lines.next()
+        .and_then(|header| header.strip_prefix(HEADER))
+        .and_then(|version| version.trim().parse::<u32>().ok())
+        .ok_or("The session file is not a blang session")?;
+    if version > VERSION {
+        return Err(format!("Session file version {} is newer than this blang supports", version));
+    }
+
+    let mut skipped = 0;
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        if restore_line(line, context).is_none() {
+            skipped += 1;
+        }
+    }
+    context.clamp_variable_selection();
+    Ok(skipped)
+}
+
+fn restore_line(line: &str, context: &mut AppContext) -> Option<()> {
+    let (key, value) = line.split_once(' ')?;
+    match key {
+        "mode" => context.current_mode = parse_mode(value)?,
+        "angle" => context.angle = parse_angle(value)?,
+        "format" => context.display.format = parse_format(value)?,
+        "base" => context.display.base = parse_base(value)?,
+        "word" => {
+            let (size, signed) = value.split_once(' ')?;
+            let size = size.parse::<u32>().ok().filter(|size| WORD_SIZES.contains(size))?;
+            context.word.size = size;
+            context.word.signed = match signed {
+                "signed" => true,
+                "unsigned" => false,
+                _ => return None,
+            };
+        },
+        "item" => {
+            let item = parse_item(value, context)?;
+            context.stack.push(item);
+        },
+        "var" => {
+            let (name, literal) = value.split_once(' ')?;
+            if !is_identifier(name) {
+                return None;
+            }
+            let item = parse_item(literal, context)?;
+            context.variables.insert(name.to_string(), item);
+        },
+        _ => return None,
+    }
+    Some(())
+}
+
+fn parse_item(literal: &str, context: &AppContext) -> Option<StackItem> {
+    match ParsedInput::create_from_buf(literal.to_string()).ok()?.input_type {
+        InputType::Value(value_type) => Some(value_to_item(value_type, &context.word)),
+        _ => None,
+    }
+}
+
+fn mode_name(mode: &AppMode) -> &'static str {
+    match mode {
+        AppMode::Stack => "stack",
+        AppMode::Program => "program",
+        AppMode::Matrix => "matrix",
+        AppMode::Variables => "variables",
+    }
+}
+
+fn parse_mode(name: &str) -> Option<AppMode> {
+    match name {
+        "stack" => Some(AppMode::Stack),
+        "program" => Some(AppMode::Program),
+        "matrix" => Some(AppMode::Matrix),
+        "variables" => Some(AppMode::Variables),
+        _ => None,
+    }
+}
+
+fn parse_angle(name: &str) -> Option<AngleMode> {
+    match name {
+        "deg" => Some(AngleMode::Deg),
+        "rad" => Some(AngleMode::Rad),
+        "grad" => Some(AngleMode::Grad),
+        _ => None,
+    }
+}
+
+fn parse_format(text: &str) -> Option<NumberFormat> {
+    if text == "std" {
+        return Some(NumberFormat::Std);
+    }
+    let (name, digits) = text.split_once(' ')?;
+    let digits = digits.parse::<usize>().ok().filter(|digits| *digits <= 17)?;
+    match name {
+        "fix" => Some(NumberFormat::Fix(digits)),
+        "sci" => Some(NumberFormat::Sci(digits)),
+        "eng" => Some(NumberFormat::Eng(digits)),
+        _ => None,
+    }
+}
+
+fn base_name(base: Base) -> &'static str {
+    match base {
+        Base::Hex => "hex",
+        Base::Dec => "dec",
+        Base::Oct => "oct",
+        Base::Bin => "bin",
+    }
+}
+
+fn parse_base(name: &str) -> Option<Base> {
+    match name {
+        "hex" => Some(Base::Hex),
+        "dec" => Some(Base::Dec),
+        "oct" => Some(Base::Oct),
+        "bin" => Some(Base::Bin),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_newer_and_foreign_files() {
+        let mut context = AppContext::default();
+        assert!(restore("blang-session 2\nitem 1\n", &mut context).is_err());
+        assert!(restore("something else\nitem 1\n", &mut context).is_err());
+        assert!(restore("", &mut context).is_err());
+        assert!(context.stack.is_empty());
+    }
+
+    #[test]
+    fn rendered_session_restores() {
+        let mut context = AppContext::default();
+        context.stack.push(StackItem::Number(-2.5));
+        context.variables.insert("x".to_string(), StackItem::String("hi there".to_string()));
+        context.angle = AngleMode::Deg;
+        let text = render(&context);
+
+        let mut restored = AppContext::default();
+        assert_eq!(restore(&text, &mut restored), Ok(0));
+        assert!(restored.matches(&context.snapshot()));
+    }
+}
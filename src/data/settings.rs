@@ -0,0 +1,108 @@
+// src/data/settings
+
+use crate::math::word::Base;
+
+// How values are rendered on screen
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct DisplaySettings {
+    pub base: Base,
+    pub format: NumberFormat,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        DisplaySettings { base: Base::Hex, format: NumberFormat::Std }
+    }
+}
+
+// How floats are written; the digit counts are places after the decimal point
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum NumberFormat {
+    // shortest text that reads back as the same float
+    Std,
+    Fix(usize),
+    Sci(usize),
+    // like Sci with the exponent a multiple of three
+    Eng(usize),
+}
+
+impl NumberFormat {
+    pub(crate) fn label(&self) -> String {
+        match self {
+            NumberFormat::Std => "STD".to_string(),
+            NumberFormat::Fix(digits) => format!("FIX {}", digits),
+            NumberFormat::Sci(digits) => format!("SCI {}", digits),
+            NumberFormat::Eng(digits) => format!("ENG {}", digits),
+        }
+    }
+}
+
+// The word size and signedness given to newly entered binary integers
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct WordSettings {
+    pub size: u32,
+    pub signed: bool,
+}
+
+impl Default for WordSettings {
+    fn default() -> Self {
+        WordSettings { size: 64, signed: false }
+    }
+}
+
+// The unit trigonometric functions take and return angles in
+#[derive(Clone, Copy, PartialEq, Default)]
+pub(crate) enum AngleMode {
+    Deg,
+    #[default]
+    Rad,
+    Grad,
+}
+
+impl AngleMode {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            AngleMode::Deg => "DEG",
+            AngleMode::Rad => "RAD",
+            AngleMode::Grad => "GRAD",
+        }
+    }
+
+    pub(crate) fn to_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Deg => angle.to_radians(),
+            AngleMode::Rad => angle,
+            AngleMode::Grad => angle * std::f64::consts::PI / 200.0,
+        }
+    }
+
+    // Sine and cosine of an angle in this mode. In DEG and GRAD the angle is first
+    // reduced to within 45 degrees of a right angle, which is exact, so multiples of
+    // a right angle give exact results (sin 180 is 0, not 1.2e-16). Radians have no
+    // such exact multiples: sin pi stays 1.2e-16.
+    pub(crate) fn sin_cos(self, angle: f64) -> (f64, f64) {
+        let quarter = match self {
+            AngleMode::Deg => 90.0,
+            AngleMode::Grad => 100.0,
+            AngleMode::Rad => return (angle.sin(), angle.cos()),
+        };
+        let turn = angle.rem_euclid(4.0 * quarter);
+        let quadrant = (turn / quarter).round();
+        let (sin, cos) = self.to_radians(turn - quadrant * quarter).sin_cos();
+        // adding zero turns the -0.0 of a negated exact zero into 0.0
+        match quadrant as u8 % 4 {
+            0 => (sin + 0.0, cos + 0.0),
+            1 => (cos + 0.0, -sin + 0.0),
+            2 => (-sin + 0.0, -cos + 0.0),
+            _ => (-cos + 0.0, sin + 0.0),
+        }
+    }
+
+    pub(crate) fn radians_to_mode(self, radians: f64) -> f64 {
+        match self {
+            AngleMode::Deg => radians.to_degrees(),
+            AngleMode::Rad => radians,
+            AngleMode::Grad => radians * 200.0 / std::f64::consts::PI,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+// src/data/status
+
+#[derive(PartialEq, Clone, Copy)]
+pub(crate) enum MessageKind {
+    Error,
+    Warning,
+    Info,
+}
+
+impl MessageKind {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            MessageKind::Error => "error",
+            MessageKind::Warning => "warning",
+            MessageKind::Info => "info",
+        }
+    }
+}
+
+// A single line of feedback shown above the input area until the next keystroke
+pub(crate) struct StatusMessage {
+    pub kind: MessageKind,
+    pub text: String,
+}
+
+impl StatusMessage {
+    pub(crate) fn new(kind: MessageKind, text: impl Into<String>) -> StatusMessage {
+        StatusMessage { kind, text: text.into() }
+    }
+}
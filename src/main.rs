@@ -4,20 +4,20 @@
 //      0.
 //      1.   stack view -- impl mode behavior to select stack items, commands (for all types)
 //      2.   program view, text editing capabilites (unless just load files)
-//      3.   variables view, arrow key to navigate, enter to select and push
-//      4.   matrix view, arrow keys to navigate, input buffer routed to cells
 // refactor:
 
 #![allow(dead_code)]
 #![allow(unused)]
 
 mod data;
+mod math;
 mod stack;
 mod ui;
 mod utils;
 mod control;
 
 use std::io::{stdout, Stdout};
+use std::time::Instant;
 use crossterm::cursor::{Hide, Show};
 use crossterm::event::{poll, read};
 use crossterm::ExecutableCommand;
@@ -25,6 +25,7 @@ use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 
 use data::context::AppContext;
 use data::context::AppMode;
+use data::session;
 use control::{flow, parsing, visualization};
 use ui::drawables::{BorderDrawer, Drawable, InputAreaUpdater, MainAreaUpdater};
 use ui::text_formatting::{print_formatted_at, TextFormat};
@@ -33,10 +34,27 @@ use utils::misc::LoopControl;
 fn main() {
     let mut stdout = stdout();
     let mut context = AppContext::default();
+    // pick up where the last run left off
+    let saving = match session::load(&mut context) {
+        Ok(warning) => {
+            if let Some(warning) = warning {
+                context.report_warning(warning);
+            }
+            true
+        },
+        Err(e) => {
+            // a newer or foreign file is left as it is rather than overwritten
+            context.report_warning(format!("{}; this session will not be saved", e));
+            false
+        },
+    };
 
     init(&mut stdout);
-    program_loop(&mut context, &mut stdout);
+    let saved = program_loop(&mut context, &mut stdout, saving);
     tini(&mut stdout);
+    if let Err(e) = saved {
+        println!("session not saved: {}", e);
+    }
 }
 
 fn init(stdout: &mut Stdout) {
@@ -44,9 +62,11 @@ fn init(stdout: &mut Stdout) {
     stdout.execute(Hide).unwrap();
 }
 
-fn program_loop(context: &mut AppContext, stdout: &mut Stdout) {
+// Runs until quit, then saves the session (if saving is on) and returns the outcome of that save
+fn program_loop(context: &mut AppContext, stdout: &mut Stdout, saving: bool) -> Result<(), String> {
     // initial graphics update
     visualization::update_graphics(stdout, context);
+    let mut last_save = Instant::now();
 
     loop {
         // check for events at 60hz
@@ -56,12 +76,22 @@ fn program_loop(context: &mut AppContext, stdout: &mut Stdout) {
             // exit condition
             match context.should_quit {
                 LoopControl::Continue => {
+                    // autosave now and then, so a crash doesn't lose the session
+                    if saving && last_save.elapsed() >= session::AUTOSAVE_INTERVAL {
+                        if let Err(e) = session::save(context) {
+                            context.report_warning(e);
+                        }
+                        last_save = Instant::now();
+                    }
                     // refresh
                     visualization::update_graphics(stdout, context);
                 }
                 LoopControl::Break => {
                     // quit program
-                    break
+                    if !saving {
+                        return Err("the existing session file could not be read, so it was left untouched".to_string());
+                    }
+                    return session::save(context);
                 }
             }
         }
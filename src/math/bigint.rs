@@ -0,0 +1,396 @@
+// Arbitrary-precision signed integers, stored as a sign and little-endian base 2^32 limbs
+
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BigInt {
+    negative: bool,
+    // no trailing zero limbs; zero is the empty vector and is never negative
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    pub(crate) fn zero() -> BigInt {
+        BigInt { negative: false, magnitude: Vec::new() }
+    }
+
+    pub(crate) fn one() -> BigInt {
+        BigInt::from(1i64)
+    }
+
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> BigInt {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        let negative = negative && !magnitude.is_empty();
+        BigInt { negative, magnitude }
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub(crate) fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub(crate) fn abs(&self) -> BigInt {
+        BigInt::from_parts(false, self.magnitude.clone())
+    }
+
+    // Parses an optionally signed string of decimal digits
+    pub(crate) fn parse(text: &str) -> Option<BigInt> {
+        let (negative, digits) = match text.as_bytes().first()? {
+            b'-' => (true, &text[1..]),
+            b'+' => (false, &text[1..]),
+            _ => (false, text),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let mut magnitude = Vec::new();
+        // consume nine digits at a time, multiplying the accumulated value by 10^9
+        for chunk in digits.as_bytes().rchunks(9).rev() {
+            let chunk_value: u32 = std::str::from_utf8(chunk).unwrap().parse().unwrap();
+            let scale = 10u32.pow(chunk.len() as u32);
+            mul_small_add(&mut magnitude, scale, chunk_value);
+        }
+        Some(BigInt::from_parts(negative, magnitude))
+    }
+
+    // Exact conversion of an integral float; None for fractional or non-finite values
+    pub(crate) fn from_f64(value: f64) -> Option<BigInt> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return None;
+        }
+        let bits = value.abs().to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+        if exponent == 0 {
+            return Some(BigInt::zero());
+        }
+        let mantissa = (bits & ((1u64 << 52) - 1)) | (1u64 << 52);
+        let shift = exponent - 1075;
+        let mut result = if shift >= 0 {
+            BigInt::from(mantissa).shl(shift as usize)
+        } else {
+            BigInt::from(mantissa >> -shift)
+        };
+        result.negative = value < 0.0 && !result.is_zero();
+        Some(result)
+    }
+
+    // Nearest float (infinite if out of range). Only the top 64 bits are converted, with
+    // the lowest of them set if anything below is non-zero, so the value rounds once.
+    pub(crate) fn to_f64(&self) -> f64 {
+        let length = self.bit_length();
+        let magnitude = if length <= 64 {
+            self.magnitude.iter().rev().fold(0u64, |acc, &limb| (acc << 32) | limb as u64) as f64
+        } else {
+            let shift = length - 64;
+            let (limb, offset) = (shift / 32, shift % 32);
+            let window = self.magnitude[limb..].iter().take(3).rev().fold(0u128, |acc, &limb| (acc << 32) | limb as u128);
+            let sticky = self.magnitude[..limb].iter().any(|&limb| limb != 0) || window & ((1 << offset) - 1) != 0;
+            let top = (window >> offset) as u64 | sticky as u64;
+            top as f64 * 2f64.powi(shift.min(i32::MAX as usize) as i32)
+        };
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    // Value as i64 if it fits
+    pub(crate) fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let magnitude = self.magnitude.iter().rev().fold(0u64, |acc, &limb| (acc << 32) | limb as u64);
+        if self.negative {
+            if magnitude <= i64::MAX as u64 + 1 { Some((magnitude as i64).wrapping_neg()) } else { None }
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    // Low 64 bits of the two's complement representation, as a cast would give
+    pub(crate) fn to_u64_wrapping(&self) -> u64 {
+        let low = self.magnitude.iter().take(2).rev().fold(0u64, |acc, &limb| (acc << 32) | limb as u64);
+        if self.negative { low.wrapping_neg() } else { low }
+    }
+
+    pub(crate) fn bit_length(&self) -> usize {
+        match self.magnitude.last() {
+            Some(&top) => (self.magnitude.len() - 1) * 32 + (32 - top.leading_zeros() as usize),
+            None => 0,
+        }
+    }
+
+    fn shl(&self, bits: usize) -> BigInt {
+        let limbs = bits / 32;
+        let offset = bits % 32;
+        let mut magnitude = vec![0u32; limbs];
+        let mut carry = 0u32;
+        for &limb in &self.magnitude {
+            if offset == 0 {
+                magnitude.push(limb);
+            } else {
+                magnitude.push((limb << offset) | carry);
+                carry = limb >> (32 - offset);
+            }
+        }
+        if carry != 0 {
+            magnitude.push(carry);
+        }
+        BigInt::from_parts(self.negative, magnitude)
+    }
+
+    // Truncating division: the quotient rounds toward zero and the remainder takes the dividend's sign
+    pub(crate) fn div_rem(&self, divisor: &BigInt) -> Result<(BigInt, BigInt), String> {
+        if divisor.is_zero() {
+            return Err("Division by zero".to_string());
+        }
+        if cmp_magnitude(&self.magnitude, &divisor.magnitude) == Ordering::Less {
+            return Ok((BigInt::zero(), self.clone()));
+        }
+        let (quotient, remainder) = if divisor.magnitude.len() == 1 {
+            let (q, r) = div_rem_small(&self.magnitude, divisor.magnitude[0]);
+            (q, vec![r])
+        } else {
+            div_rem_magnitude(&self.magnitude, &divisor.magnitude)
+        };
+        Ok((
+            BigInt::from_parts(self.negative != divisor.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+
+    pub(crate) fn gcd(&self, other: &BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = other.abs();
+        while !b.is_zero() {
+            let (_, remainder) = a.div_rem(&b).unwrap();
+            a = b;
+            b = remainder;
+        }
+        a
+    }
+
+    pub(crate) fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut result = BigInt::one();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> BigInt {
+        let magnitude = value.unsigned_abs();
+        BigInt::from_parts(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(value: u64) -> BigInt {
+        BigInt::from_parts(false, vec![value as u32, (value >> 32) as u32])
+    }
+}
+
+impl std::fmt::Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // peel off nine decimal digits at a time
+        let mut chunks = Vec::new();
+        let mut magnitude = self.magnitude.clone();
+        while !magnitude.is_empty() {
+            let (quotient, remainder) = div_rem_small(&magnitude, 1_000_000_000);
+            chunks.push(remainder);
+            magnitude = quotient;
+            while magnitude.last() == Some(&0) {
+                magnitude.pop();
+            }
+        }
+        let mut text = String::new();
+        if self.negative {
+            text.push('-');
+        }
+        text.push_str(&chunks.pop().unwrap().to_string());
+        for chunk in chunks.iter().rev() {
+            text.push_str(&format!("{:09}", chunk));
+        }
+        f.pad(&text)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::ops::Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl std::ops::Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, rhs: &BigInt) -> BigInt {
+        if self.negative == rhs.negative {
+            return BigInt::from_parts(self.negative, add_magnitude(&self.magnitude, &rhs.magnitude));
+        }
+        // opposite signs: subtract the smaller magnitude from the larger
+        match cmp_magnitude(&self.magnitude, &rhs.magnitude) {
+            Ordering::Less => BigInt::from_parts(rhs.negative, sub_magnitude(&rhs.magnitude, &self.magnitude)),
+            _ => BigInt::from_parts(self.negative, sub_magnitude(&self.magnitude, &rhs.magnitude)),
+        }
+    }
+}
+
+impl std::ops::Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, rhs: &BigInt) -> BigInt {
+        self + &(-rhs)
+    }
+}
+
+impl std::ops::Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, rhs: &BigInt) -> BigInt {
+        if self.is_zero() || rhs.is_zero() {
+            return BigInt::zero();
+        }
+        let mut product = vec![0u32; self.magnitude.len() + rhs.magnitude.len()];
+        for (i, &a) in self.magnitude.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in rhs.magnitude.iter().enumerate() {
+                let current = product[i + j] as u64 + a as u64 * b as u64 + carry;
+                product[i + j] = current as u32;
+                carry = current >> 32;
+            }
+            product[i + rhs.magnitude.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != rhs.negative, product)
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let current = limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(current as u32);
+        carry = current >> 32;
+    }
+    if carry != 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+// a - b where |a| >= |b|
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut current = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if current < 0 {
+            current += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(current as u32);
+    }
+    difference
+}
+
+// magnitude = magnitude * factor + addend
+fn mul_small_add(magnitude: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for limb in magnitude.iter_mut() {
+        let current = *limb as u64 * factor as u64 + carry;
+        *limb = current as u32;
+        carry = current >> 32;
+    }
+    if carry != 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+fn div_rem_small(magnitude: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0u32; magnitude.len()];
+    let mut remainder = 0u64;
+    for i in (0..magnitude.len()).rev() {
+        let current = (remainder << 32) | magnitude[i] as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    (quotient, remainder as u32)
+}
+
+// Schoolbook binary long division; plenty fast for calculator-sized numbers
+fn div_rem_magnitude(dividend: &[u32], divisor: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut quotient = vec![0u32; dividend.len()];
+    let mut remainder: Vec<u32> = Vec::with_capacity(divisor.len() + 1);
+    for bit in (0..dividend.len() * 32).rev() {
+        // remainder = remainder * 2 + next bit of the dividend
+        let next_bit = (dividend[bit / 32] >> (bit % 32)) & 1;
+        mul_small_add(&mut remainder, 2, next_bit);
+        if cmp_magnitude(&remainder, divisor) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, divisor);
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_f64_rounds_to_the_nearest_float() {
+        let cases = [
+            "0", "-1", "9007199254740993", "18446744073709551615", "18446744073709553665",
+            "-18446744073709553664", "36893488147419107329", "123456789012345678901234567890",
+            "-170141183460469231731687303715884105727", "1361129467683754004969225881564309618688",
+        ];
+        for text in cases {
+            assert_eq!(BigInt::parse(text).unwrap().to_f64(), text.parse::<f64>().unwrap(), "{}", text);
+        }
+        assert_eq!(BigInt::from(2u64).pow(1024).to_f64(), f64::INFINITY);
+        assert_eq!(BigInt::from(2u64).pow(1023).to_f64(), 2f64.powi(1023));
+    }
+}
@@ -0,0 +1,239 @@
+// Complex scalars in rectangular form
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub(crate) fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+
+    pub(crate) fn from_polar(r: f64, theta: f64) -> Complex {
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+
+    pub(crate) fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    // Angle from the positive real axis in radians, in (-pi, pi]
+    pub(crate) fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub(crate) fn conj(self) -> Complex {
+        Complex::new(self.re, -self.im)
+    }
+
+    // Principal square root, computed without going through polar form so that
+    // e.g. sqrt(-4) is exactly 2i
+    pub(crate) fn sqrt(self) -> Complex {
+        if self.re == 0.0 && self.im == 0.0 {
+            return Complex::new(0.0, 0.0);
+        }
+        let t = ((self.re.abs() + self.abs()) / 2.0).sqrt();
+        if self.re >= 0.0 {
+            Complex::new(t, self.im / (2.0 * t))
+        } else {
+            Complex::new(self.im.abs() / (2.0 * t), t.copysign(self.im))
+        }
+    }
+
+    // Principal natural logarithm
+    pub(crate) fn ln(self) -> Complex {
+        Complex::new(self.abs().ln(), self.arg())
+    }
+
+    pub(crate) fn exp(self) -> Complex {
+        Complex::from_polar(self.re.exp(), self.im)
+    }
+
+    pub(crate) fn sin(self) -> Complex {
+        Complex::new(self.re.sin() * self.im.cosh(), self.re.cos() * self.im.sinh())
+    }
+
+    pub(crate) fn cos(self) -> Complex {
+        Complex::new(self.re.cos() * self.im.cosh(), -self.re.sin() * self.im.sinh())
+    }
+
+    pub(crate) fn tan(self) -> Complex {
+        self.sin() / self.cos()
+    }
+
+    pub(crate) fn sinh(self) -> Complex {
+        Complex::new(self.re.sinh() * self.im.cos(), self.re.cosh() * self.im.sin())
+    }
+
+    pub(crate) fn cosh(self) -> Complex {
+        Complex::new(self.re.cosh() * self.im.cos(), self.re.sinh() * self.im.sin())
+    }
+
+    pub(crate) fn tanh(self) -> Complex {
+        self.sinh() / self.cosh()
+    }
+
+    // Principal inverses, from their logarithmic forms
+    pub(crate) fn asin(self) -> Complex {
+        // asin z = -i ln(iz + sqrt(1 - z²))
+        let i = Complex::new(0.0, 1.0);
+        -(i * (i * self + (Complex::from(1.0) - self * self).sqrt()).ln())
+    }
+
+    pub(crate) fn acos(self) -> Complex {
+        Complex::from(std::f64::consts::FRAC_PI_2) - self.asin()
+    }
+
+    pub(crate) fn atan(self) -> Complex {
+        // atan z = i/2 (ln(1 - iz) - ln(1 + iz))
+        let i = Complex::new(0.0, 1.0);
+        let one = Complex::from(1.0);
+        Complex::new(0.0, 0.5) * ((one - i * self).ln() - (one + i * self).ln())
+    }
+
+    pub(crate) fn asinh(self) -> Complex {
+        (self + (self * self + Complex::from(1.0)).sqrt()).ln()
+    }
+
+    pub(crate) fn acosh(self) -> Complex {
+        let one = Complex::from(1.0);
+        (self + (self + one).sqrt() * (self - one).sqrt()).ln()
+    }
+
+    pub(crate) fn atanh(self) -> Complex {
+        let one = Complex::from(1.0);
+        Complex::from(0.5) * ((one + self).ln() - (one - self).ln())
+    }
+
+    // Zero to a power with a negative real part is infinite, as it is for real
+    // numbers; with a zero real part and an imaginary part it has no value at all
+    pub(crate) fn pow(self, exponent: Complex) -> Complex {
+        if exponent == Complex::new(0.0, 0.0) {
+            return Complex::new(1.0, 0.0);
+        }
+        if self == Complex::new(0.0, 0.0) {
+            return match exponent.re {
+                re if re > 0.0 => self,
+                re if re < 0.0 => Complex::new(f64::INFINITY, 0.0),
+                _ => Complex::new(f64::NAN, f64::NAN),
+            };
+        }
+        (exponent * self.ln()).exp()
+    }
+
+    pub(crate) fn checked_div(self, rhs: Complex) -> Result<Complex, String> {
+        if rhs.re == 0.0 && rhs.im == 0.0 {
+            Err("Division by zero".to_string())
+        } else {
+            Ok(self / rhs)
+        }
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Complex {
+        Complex::new(re, 0.0)
+    }
+}
+
+impl std::ops::Add for Complex {
+    type Output = Complex;
+
+    fn add(self, rhs: Complex) -> Complex {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl std::ops::Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, rhs: Complex) -> Complex {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl std::ops::Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, rhs: Complex) -> Complex {
+        Complex::new(self.re * rhs.re - self.im * rhs.im, self.re * rhs.im + self.im * rhs.re)
+    }
+}
+
+impl std::ops::Div for Complex {
+    type Output = Complex;
+
+    // Smith's algorithm, which avoids overflow for large components
+    fn div(self, rhs: Complex) -> Complex {
+        if rhs.re.abs() >= rhs.im.abs() {
+            let ratio = rhs.im / rhs.re;
+            let denominator = rhs.re + ratio * rhs.im;
+            Complex::new((self.re + self.im * ratio) / denominator, (self.im - self.re * ratio) / denominator)
+        } else {
+            let ratio = rhs.re / rhs.im;
+            let denominator = rhs.im + ratio * rhs.re;
+            Complex::new((self.re * ratio + self.im) / denominator, (self.im * ratio - self.re) / denominator)
+        }
+    }
+}
+
+impl std::ops::Neg for Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{E, FRAC_PI_2, PI};
+
+    fn close(a: Complex, b: Complex) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn powers() {
+        let i = Complex::new(0.0, 1.0);
+        assert!(close(i.pow(Complex::from(2.0)), Complex::from(-1.0)));
+        assert!(close(Complex::new(1.0, 1.0).pow(Complex::from(2.0)), Complex::new(0.0, 2.0)));
+        // i^i is real
+        assert!(close(i.pow(i), Complex::from((-FRAC_PI_2).exp())));
+        // the principal cube root of -8
+        assert!(close(Complex::from(-8.0).pow(Complex::from(1.0 / 3.0)), Complex::new(1.0, 3f64.sqrt())));
+        assert_eq!(Complex::new(3.0, 4.0).pow(Complex::from(0.0)), Complex::from(1.0));
+    }
+
+    #[test]
+    fn powers_of_zero() {
+        let zero = Complex::from(0.0);
+        assert_eq!(zero.pow(Complex::new(2.0, 1.0)), zero);
+        assert_eq!(zero.pow(Complex::from(-1.0)).re, f64::INFINITY);
+        assert_eq!(zero.pow(Complex::new(-0.5, 3.0)).re, f64::INFINITY);
+        assert!(zero.pow(Complex::new(0.0, 1.0)).re.is_nan());
+        assert_eq!(zero.pow(zero), Complex::from(1.0));
+    }
+
+    #[test]
+    fn ln_and_exp_are_inverse() {
+        assert!(close(Complex::from(1.0).exp(), Complex::from(E)));
+        assert!(close(Complex::new(0.0, PI).exp(), Complex::from(-1.0)));
+        assert!(close(Complex::from(-1.0).ln(), Complex::new(0.0, PI)));
+        for z in [Complex::new(2.0, -3.0), Complex::new(-0.5, 0.25), Complex::new(1e-3, 7.0)] {
+            assert!(close(z.ln().exp(), z));
+        }
+    }
+
+    #[test]
+    fn polar_form() {
+        let z = Complex::new(-3.0, 4.0);
+        assert_eq!(z.abs(), 5.0);
+        assert!(close(Complex::from_polar(z.abs(), z.arg()), z));
+        assert!(close(Complex::from_polar(2.0, FRAC_PI_2), Complex::new(0.0, 2.0)));
+        assert_eq!(Complex::from(-1.0).arg(), PI);
+    }
+}
@@ -0,0 +1,58 @@
+// Catalog of mathematical and physical constants (CODATA 2018 values). Physical
+// constants carry their SI unit so they combine with quantities.
+
+pub(crate) struct Constant {
+    pub name: &'static str,
+    // other spellings, typically the Greek or subscripted symbol
+    pub aliases: &'static [&'static str],
+    pub value: f64,
+    // unit expression as accepted by Unit::parse, empty for pure numbers
+    pub unit: &'static str,
+    pub description: &'static str,
+}
+
+const fn constant(
+    name: &'static str,
+    aliases: &'static [&'static str],
+    value: f64,
+    unit: &'static str,
+    description: &'static str,
+) -> Constant {
+    Constant { name, aliases, value, unit, description }
+}
+
+pub(crate) const CONSTANTS: &[Constant] = &[
+    // mathematics
+    constant("pi", &["π"], std::f64::consts::PI, "", "Ratio of a circle's circumference to its diameter"),
+    constant("tau", &["τ"], std::f64::consts::TAU, "", "Full turn in radians, 2π"),
+    constant("e", &[], std::f64::consts::E, "", "Base of the natural logarithm"),
+    constant("phi", &["φ"], 1.618033988749895, "", "Golden ratio"),
+    constant("gamma", &["γ"], 0.5772156649015329, "", "Euler-Mascheroni constant"),
+    // universal
+    constant("c", &[], 299792458.0, "m/s", "Speed of light in vacuum"),
+    constant("h", &[], 6.62607015e-34, "J*s", "Planck constant"),
+    constant("hbar", &["ħ"], 1.054571817e-34, "J*s", "Reduced Planck constant"),
+    constant("G", &[], 6.67430e-11, "m^3/kg/s^2", "Newtonian constant of gravitation"),
+    constant("g0", &["g₀"], 9.80665, "m/s^2", "Standard acceleration of gravity"),
+    // electromagnetic
+    constant("qe", &["e0"], 1.602176634e-19, "C", "Elementary charge"),
+    constant("eps0", &["ε₀", "ε0"], 8.8541878128e-12, "F/m", "Vacuum electric permittivity"),
+    constant("mu0", &["μ₀", "μ0"], 1.25663706212e-6, "N/A^2", "Vacuum magnetic permeability"),
+    // atomic and nuclear
+    constant("me", &[], 9.1093837015e-31, "kg", "Electron mass"),
+    constant("mp", &[], 1.67262192369e-27, "kg", "Proton mass"),
+    constant("mn", &[], 1.67492749804e-27, "kg", "Neutron mass"),
+    constant("u", &["amu"], 1.66053906660e-27, "kg", "Atomic mass constant"),
+    constant("a0", &["a₀"], 5.29177210903e-11, "m", "Bohr radius"),
+    // physico-chemical
+    constant("kB", &["k_B", "k"], 1.380649e-23, "J/K", "Boltzmann constant"),
+    constant("NA", &["N_A"], 6.02214076e23, "1/mol", "Avogadro constant"),
+    constant("R", &[], 8.314462618, "J/mol/K", "Molar gas constant"),
+    constant("F", &[], 96485.33212, "C/mol", "Faraday constant"),
+    constant("sigma", &["σ"], 5.670374419e-8, "W/m^2/K^4", "Stefan-Boltzmann constant"),
+    constant("atm0", &[], 101325.0, "Pa", "Standard atmosphere"),
+];
+
+pub(crate) fn lookup(name: &str) -> Option<&'static Constant> {
+    CONSTANTS.iter().find(|constant| constant.name == name || constant.aliases.contains(&name))
+}
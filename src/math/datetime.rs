@@ -0,0 +1,206 @@
+// Calendar dates and durations, counted in whole seconds. Dates are naive (no
+// time zone) on the proleptic Gregorian calendar, so day arithmetic is exact.
+
+const SECONDS_PER_DAY: i64 = 86400;
+
+// Days since 1970-01-01 for a civil date (H. Hinnant's algorithm)
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// "HH:MM" or "HH:MM:SS" as seconds; hours may exceed a day
+fn parse_clock(text: &str) -> Option<Result<i64, String>> {
+    let parts: Vec<&str> = text.split(':').collect();
+    if !(2..=3).contains(&parts.len()) || parts.iter().any(|part| part.is_empty() || !part.chars().all(|ch| ch.is_ascii_digit())) {
+        return None;
+    }
+    let numbers: Vec<i64> = parts.iter().map(|part| part.parse::<i64>().unwrap_or(i64::MAX)).collect();
+    let (hours, minutes, seconds) = (numbers[0], numbers[1], numbers.get(2).copied().unwrap_or(0));
+    if minutes >= 60 || seconds >= 60 {
+        return Some(Err(format!("Invalid time: {}", text)));
+    }
+    let total = hours.checked_mul(3600)
+        .and_then(|h| h.checked_add(minutes * 60 + seconds));
+    Some(total.ok_or_else(|| format!("Time too large: {}", text)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Date {
+    // seconds since 1970-01-01T00:00:00
+    seconds: i64,
+    // written without a time of day
+    date_only: bool,
+}
+
+impl Date {
+    // Dates are limited to the four digit years the ISO form can write and parse back
+    pub(crate) fn from_seconds(seconds: i64, date_only: bool) -> Result<Date, String> {
+        let range = days_from_civil(0, 1, 1) * SECONDS_PER_DAY..days_from_civil(10000, 1, 1) * SECONDS_PER_DAY;
+        if !range.contains(&seconds) {
+            return Err("Date out of range (years 0000 to 9999)".to_string());
+        }
+        // a date-only value can only land on midnight
+        Ok(Date { seconds, date_only: date_only && seconds.rem_euclid(SECONDS_PER_DAY) == 0 })
+    }
+
+    pub(crate) fn seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    // Parses "2024-03-15", "2024-03-15T10:30" and "2024-03-15T10:30:00"
+    pub(crate) fn parse(text: &str) -> Option<Result<Date, String>> {
+        let (date, time) = match text.split_once('T') {
+            Some((date, time)) => (date, Some(time)),
+            None => (text, None),
+        };
+        let parts: Vec<&str> = date.split('-').collect();
+        if parts.len() != 3 || parts[0].len() != 4 || parts[1].len() != 2 || parts[2].len() != 2 {
+            return None;
+        }
+        let (year, month, day) = (parts[0].parse::<i64>().ok()?, parts[1].parse::<u32>().ok()?, parts[2].parse::<u32>().ok()?);
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return Some(Err(format!("Invalid date: {}", date)));
+        }
+        let midnight = days_from_civil(year, month, day) * SECONDS_PER_DAY;
+        let Some(time) = time else {
+            return Some(Ok(Date { seconds: midnight, date_only: true }));
+        };
+        let time_of_day = match parse_clock(time) {
+            Some(Ok(seconds)) if seconds < SECONDS_PER_DAY => seconds,
+            _ => return Some(Err(format!("Invalid time of day: {}", time))),
+        };
+        Some(Ok(Date { seconds: midnight + time_of_day, date_only: false }))
+    }
+
+    pub(crate) fn days(&self) -> i64 {
+        self.seconds.div_euclid(SECONDS_PER_DAY)
+    }
+
+    pub(crate) fn is_date_only(&self) -> bool {
+        self.date_only
+    }
+
+    pub(crate) fn weekday_name(&self) -> &'static str {
+        // 1970-01-01 was a Thursday
+        const NAMES: [&str; 7] = ["Thursday", "Friday", "Saturday", "Sunday", "Monday", "Tuesday", "Wednesday"];
+        NAMES[self.days().rem_euclid(7) as usize]
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (year, month, day) = civil_from_days(self.days());
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)?;
+        if !self.date_only {
+            let time_of_day = self.seconds.rem_euclid(SECONDS_PER_DAY);
+            write!(f, "T{:02}:{:02}:{:02}", time_of_day / 3600, time_of_day / 60 % 60, time_of_day % 60)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Duration {
+    seconds: i64,
+}
+
+impl Duration {
+    pub(crate) fn from_seconds(seconds: i64) -> Duration {
+        Duration { seconds }
+    }
+
+    pub(crate) fn seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    // Parses "HH:MM", "HH:MM:SS" and their negatives
+    pub(crate) fn parse(text: &str) -> Option<Result<Duration, String>> {
+        let (sign, clock) = match text.strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, text),
+        };
+        Some(parse_clock(clock)?.map(|seconds| Duration { seconds: sign * seconds }))
+    }
+
+    pub(crate) fn hours(&self) -> f64 {
+        self.seconds as f64 / 3600.0
+    }
+}
+
+impl std::fmt::Display for Duration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.seconds < 0 { "-" } else { "" };
+        let seconds = self.seconds.unsigned_abs();
+        write!(f, "{}{:02}:{:02}:{:02}", sign, seconds / 3600, seconds / 60 % 60, seconds % 60)
+    }
+}
+
+// H.MMSS (hours, minutes and seconds packed into the digits) to decimal hours
+pub(crate) fn from_hms(value: f64) -> f64 {
+    // rounding first keeps 1.3 from reading as 1:29:59.99
+    let scaled = (value.abs() * 1e10).round() / 1e6;
+    let hours = (scaled / 1e4).floor();
+    let minutes = ((scaled - hours * 1e4) / 100.0).floor();
+    let seconds = scaled - hours * 1e4 - minutes * 100.0;
+    (hours + minutes / 60.0 + seconds / 3600.0).copysign(value)
+}
+
+pub(crate) fn to_hms(value: f64) -> f64 {
+    let total = (value.abs() * 3600.0 * 1e6).round() / 1e6;
+    let hours = (total / 3600.0).floor();
+    let minutes = ((total - hours * 3600.0) / 60.0).floor();
+    let seconds = total - hours * 3600.0 - minutes * 60.0;
+    (hours + minutes / 100.0 + seconds / 1e4).copysign(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stack::item::StackItem;
+
+    #[test]
+    fn oversized_times_are_errors() {
+        assert!(Duration::parse("2562047788015215:59:59").unwrap().is_err());
+        assert!(Duration::parse("99999999999999999999:00").unwrap().is_err());
+        assert_eq!(Duration::parse("-100:30:05").unwrap().unwrap().seconds(), -361805);
+    }
+
+    #[test]
+    fn date_arithmetic_overflow_is_an_error() {
+        let date = StackItem::Date(Date::parse("2024-03-15").unwrap().unwrap());
+        let far = StackItem::Duration(Duration::from_seconds(i64::MAX));
+        let min = StackItem::Duration(Duration::from_seconds(i64::MIN));
+        assert!((date.clone() + far).is_err());
+        assert!((date.clone() - min).is_err());
+        let later = (date.clone() + StackItem::Duration(Duration::parse("48:00").unwrap().unwrap())).unwrap();
+        assert_eq!(later, StackItem::Date(Date::parse("2024-03-17").unwrap().unwrap()));
+    }
+}
@@ -0,0 +1,791 @@
+// Matrix factorizations. Each returns its factors as plain matrices so they can
+// be pushed onto the stack and checked by multiplying them back together.
+
+use crate::math::linalg::{identity, require_square, shape, transpose, Matrix};
+
+const EPSILON: f64 = f64::EPSILON;
+const MAX_SWEEPS: usize = 100;
+
+// Tolerance used to decide whether a matrix is symmetric
+fn symmetry_tolerance(a: &[Vec<f64>]) -> f64 {
+    1e-10 * a.iter().flatten().fold(1.0, |acc: f64, x| acc.max(x.abs()))
+}
+
+fn is_symmetric(a: &[Vec<f64>]) -> bool {
+    let n = a.len();
+    let tolerance = symmetry_tolerance(a);
+    (0..n).all(|i| (0..i).all(|j| (a[i][j] - a[j][i]).abs() <= tolerance))
+}
+
+// LU decomposition with partial pivoting: P A = L U, with L unit lower triangular
+pub(crate) fn lu(a: &[Vec<f64>]) -> Result<(Matrix, Matrix, Matrix), String> {
+    let n = require_square(a)?;
+    let mut u = a.to_vec();
+    let mut l = vec![vec![0.0; n]; n];
+    let mut permutation: Vec<usize> = (0..n).collect();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| u[i][col].abs().total_cmp(&u[j][col].abs()))
+            .unwrap();
+        if pivot != col {
+            u.swap(pivot, col);
+            l.swap(pivot, col);
+            permutation.swap(pivot, col);
+        }
+        // a zero pivot means the matrix is singular; U simply keeps the zero
+        if u[col][col] == 0.0 {
+            continue;
+        }
+        for row in col + 1..n {
+            let factor = u[row][col] / u[col][col];
+            l[row][col] = factor;
+            for k in col..n {
+                u[row][k] -= factor * u[col][k];
+            }
+            u[row][col] = 0.0;
+        }
+    }
+    for i in 0..n {
+        l[i][i] = 1.0;
+    }
+    let p = permutation.iter()
+        .map(|&source| (0..n).map(|j| if j == source { 1.0 } else { 0.0 }).collect())
+        .collect();
+    Ok((l, u, p))
+}
+
+// Householder QR: A = Q R with Q orthogonal (m x m) and R upper triangular (m x n)
+pub(crate) fn qr(a: &[Vec<f64>]) -> Result<(Matrix, Matrix), String> {
+    let (m, n) = shape(a);
+    if m == 0 || n == 0 {
+        return Err("Matrix is empty".to_string());
+    }
+    let mut r = a.to_vec();
+    let mut q = identity(m);
+
+    for k in 0..n.min(m - 1) {
+        let norm = (k..m).map(|i| r[i][k] * r[i][k]).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            continue;
+        }
+        let alpha = if r[k][k] > 0.0 { -norm } else { norm };
+        let mut v: Vec<f64> = (k..m).map(|i| r[i][k]).collect();
+        v[0] -= alpha;
+        let v_norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if v_norm == 0.0 {
+            continue;
+        }
+        v.iter_mut().for_each(|x| *x /= v_norm);
+
+        // R = H R
+        for j in 0..n {
+            let dot: f64 = (k..m).map(|i| v[i - k] * r[i][j]).sum();
+            for i in k..m {
+                r[i][j] -= 2.0 * v[i - k] * dot;
+            }
+        }
+        // Q = Q H
+        for i in 0..m {
+            let dot: f64 = (k..m).map(|j| q[i][j] * v[j - k]).sum();
+            for j in k..m {
+                q[i][j] -= 2.0 * dot * v[j - k];
+            }
+        }
+    }
+    for i in 0..m {
+        for j in 0..i.min(n) {
+            r[i][j] = 0.0;
+        }
+    }
+    Ok((q, r))
+}
+
+// Cholesky factorization of a symmetric positive definite matrix: A = L L^T
+pub(crate) fn cholesky(a: &[Vec<f64>]) -> Result<Matrix, String> {
+    let n = require_square(a)?;
+    if !is_symmetric(a) {
+        return Err("Matrix is not symmetric".to_string());
+    }
+    let mut l = vec![vec![0.0; n]; n];
+    for j in 0..n {
+        let diagonal = a[j][j] - (0..j).map(|k| l[j][k] * l[j][k]).sum::<f64>();
+        if diagonal <= 0.0 {
+            return Err("Matrix is not positive definite".to_string());
+        }
+        l[j][j] = diagonal.sqrt();
+        for i in j + 1..n {
+            let sum: f64 = (0..j).map(|k| l[i][k] * l[j][k]).sum();
+            l[i][j] = (a[i][j] - sum) / l[j][j];
+        }
+    }
+    Ok(l)
+}
+
+// Eigendecomposition of a symmetric matrix by cyclic Jacobi rotations.
+// Returns the eigenvalues in ascending order and the matching eigenvectors as columns.
+pub(crate) fn eigen_symmetric(a: &[Vec<f64>]) -> Result<(Vec<f64>, Matrix), String> {
+    let n = require_square(a)?;
+    if !is_symmetric(a) {
+        return Err("Matrix is not symmetric".to_string());
+    }
+    let mut m = a.to_vec();
+    let mut v = identity(n);
+
+    for _ in 0..MAX_SWEEPS {
+        let off_diagonal: f64 = (0..n).map(|i| (0..n).filter(|&j| j != i).map(|j| m[i][j] * m[i][j]).sum::<f64>()).sum();
+        let total: f64 = m.iter().flatten().map(|x| x * x).sum();
+        if off_diagonal <= EPSILON * EPSILON * total {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if m[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (m[q][q] - m[p][p]) / (2.0 * m[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (mkp, mkq) = (m[k][p], m[k][q]);
+                    m[k][p] = c * mkp - s * mkq;
+                    m[k][q] = s * mkp + c * mkq;
+                }
+                for k in 0..n {
+                    let (mpk, mqk) = (m[p][k], m[q][k]);
+                    m[p][k] = c * mpk - s * mqk;
+                    m[q][k] = s * mpk + c * mqk;
+                }
+                for k in 0..n {
+                    let (vkp, vkq) = (v[k][p], v[k][q]);
+                    v[k][p] = c * vkp - s * vkq;
+                    v[k][q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| m[i][i].total_cmp(&m[j][j]));
+    let values = order.iter().map(|&i| m[i][i]).collect();
+    let vectors = (0..n).map(|row| order.iter().map(|&col| v[row][col]).collect()).collect();
+    Ok((values, vectors))
+}
+
+// Eigendecomposition of a general real matrix: reduction to Hessenberg form followed by
+// the shifted QR algorithm (after the JAMA/EISPACK orthes and hqr2 routines).
+// Returns the real and imaginary parts of the eigenvalues and the eigenvector matrix V.
+// For a complex pair at (k, k+1), columns k and k+1 of V hold the real and imaginary
+// parts of the eigenvector belonging to the eigenvalue with positive imaginary part.
+pub(crate) fn eigen_general(a: &[Vec<f64>]) -> Result<(Vec<f64>, Vec<f64>, Matrix), String> {
+    let n = require_square(a)?;
+    let mut h = a.to_vec();
+    let mut v = identity(n);
+    orthes(&mut h, &mut v);
+    let (d, e) = hqr2(&mut h, &mut v)?;
+    Ok((d, e, v))
+}
+
+// Householder reduction to upper Hessenberg form, accumulating the transformations in v
+fn orthes(h: &mut [Vec<f64>], v: &mut [Vec<f64>]) {
+    let n = h.len();
+    if n < 3 {
+        return;
+    }
+    let high = n - 1;
+    let mut ort = vec![0.0; n];
+
+    for m in 1..high {
+        let scale: f64 = (m..=high).map(|i| h[i][m - 1].abs()).sum();
+        if scale == 0.0 {
+            continue;
+        }
+        let mut sum = 0.0;
+        for i in (m..=high).rev() {
+            ort[i] = h[i][m - 1] / scale;
+            sum += ort[i] * ort[i];
+        }
+        let mut g = sum.sqrt();
+        if ort[m] > 0.0 {
+            g = -g;
+        }
+        sum -= ort[m] * g;
+        ort[m] -= g;
+
+        for j in m..n {
+            let f = (m..=high).rev().map(|i| ort[i] * h[i][j]).sum::<f64>() / sum;
+            for i in m..=high {
+                h[i][j] -= f * ort[i];
+            }
+        }
+        for i in 0..=high {
+            let f = (m..=high).rev().map(|j| ort[j] * h[i][j]).sum::<f64>() / sum;
+            for j in m..=high {
+                h[i][j] -= f * ort[j];
+            }
+        }
+        ort[m] *= scale;
+        h[m][m - 1] = scale * g;
+    }
+
+    for m in (1..high).rev() {
+        if h[m][m - 1] == 0.0 {
+            continue;
+        }
+        for i in m + 1..=high {
+            ort[i] = h[i][m - 1];
+        }
+        for j in m..=high {
+            let g = (m..=high).map(|i| ort[i] * v[i][j]).sum::<f64>() / ort[m] / h[m][m - 1];
+            for i in m..=high {
+                v[i][j] += g * ort[i];
+            }
+        }
+    }
+}
+
+// Complex scalar division (xr + i xi) / (yr + i yi)
+fn cdiv(xr: f64, xi: f64, yr: f64, yi: f64) -> (f64, f64) {
+    if yr.abs() > yi.abs() {
+        let r = yi / yr;
+        let d = yr + r * yi;
+        ((xr + r * xi) / d, (xi - r * xr) / d)
+    } else {
+        let r = yr / yi;
+        let d = yi + r * yr;
+        ((r * xr + xi) / d, (r * xi - xr) / d)
+    }
+}
+
+// Shifted QR iteration on an upper Hessenberg matrix, then back substitution for the eigenvectors
+fn hqr2(h: &mut [Vec<f64>], v: &mut [Vec<f64>]) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let nn = h.len();
+    let mut d = vec![0.0; nn];
+    let mut e = vec![0.0; nn];
+    let high = nn - 1;
+    let mut exshift = 0.0;
+    let (mut p, mut q, mut r, mut s, mut z) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    let (mut t, mut w, mut x, mut y);
+
+    let mut norm = 0.0;
+    for i in 0..nn {
+        for j in i.saturating_sub(1)..nn {
+            norm += h[i][j].abs();
+        }
+    }
+
+    let mut n = nn as isize - 1;
+    let mut iterations = 0;
+    while n >= 0 {
+        let nu = n as usize;
+
+        // look for a single small sub-diagonal element
+        let mut l = nu;
+        while l > 0 {
+            s = h[l - 1][l - 1].abs() + h[l][l].abs();
+            if s == 0.0 {
+                s = norm;
+            }
+            if h[l][l - 1].abs() < EPSILON * s {
+                break;
+            }
+            l -= 1;
+        }
+
+        if l == nu {
+            // one root found
+            h[nu][nu] += exshift;
+            d[nu] = h[nu][nu];
+            e[nu] = 0.0;
+            n -= 1;
+            iterations = 0;
+        } else if l + 1 == nu {
+            // two roots found
+            w = h[nu][nu - 1] * h[nu - 1][nu];
+            p = (h[nu - 1][nu - 1] - h[nu][nu]) / 2.0;
+            q = p * p + w;
+            z = q.abs().sqrt();
+            h[nu][nu] += exshift;
+            h[nu - 1][nu - 1] += exshift;
+            x = h[nu][nu];
+
+            if q >= 0.0 {
+                // real pair
+                z = if p >= 0.0 { p + z } else { p - z };
+                d[nu - 1] = x + z;
+                d[nu] = d[nu - 1];
+                if z != 0.0 {
+                    d[nu] = x - w / z;
+                }
+                e[nu - 1] = 0.0;
+                e[nu] = 0.0;
+                x = h[nu][nu - 1];
+                s = x.abs() + z.abs();
+                p = x / s;
+                q = z / s;
+                r = (p * p + q * q).sqrt();
+                p /= r;
+                q /= r;
+
+                for j in nu - 1..nn {
+                    z = h[nu - 1][j];
+                    h[nu - 1][j] = q * z + p * h[nu][j];
+                    h[nu][j] = q * h[nu][j] - p * z;
+                }
+                for i in 0..=nu {
+                    z = h[i][nu - 1];
+                    h[i][nu - 1] = q * z + p * h[i][nu];
+                    h[i][nu] = q * h[i][nu] - p * z;
+                }
+                for i in 0..=high {
+                    z = v[i][nu - 1];
+                    v[i][nu - 1] = q * z + p * v[i][nu];
+                    v[i][nu] = q * v[i][nu] - p * z;
+                }
+            } else {
+                // complex pair
+                d[nu - 1] = x + p;
+                d[nu] = x + p;
+                e[nu - 1] = z;
+                e[nu] = -z;
+            }
+            n -= 2;
+            iterations = 0;
+        } else {
+            // no convergence yet
+            x = h[nu][nu];
+            y = h[nu - 1][nu - 1];
+            w = h[nu][nu - 1] * h[nu - 1][nu];
+
+            // Wilkinson's original ad hoc shift
+            if iterations == 10 {
+                exshift += x;
+                for i in 0..=nu {
+                    h[i][i] -= x;
+                }
+                s = h[nu][nu - 1].abs() + h[nu - 1][nu - 2].abs();
+                x = 0.75 * s;
+                y = x;
+                w = -0.4375 * s * s;
+            }
+
+            // MATLAB's ad hoc shift
+            if iterations == 30 {
+                s = (y - x) / 2.0;
+                s = s * s + w;
+                if s > 0.0 {
+                    s = s.sqrt();
+                    if y < x {
+                        s = -s;
+                    }
+                    s = x - w / ((y - x) / 2.0 + s);
+                    for i in 0..=nu {
+                        h[i][i] -= s;
+                    }
+                    exshift += s;
+                    x = 0.964;
+                    y = x;
+                    w = x;
+                }
+            }
+
+            iterations += 1;
+            if iterations > 30 * nn + 100 {
+                return Err("Eigenvalue iteration did not converge".to_string());
+            }
+
+            // look for two consecutive small sub-diagonal elements
+            let mut m = nu - 2;
+            loop {
+                z = h[m][m];
+                r = x - z;
+                s = y - z;
+                p = (r * s - w) / h[m + 1][m] + h[m][m + 1];
+                q = h[m + 1][m + 1] - z - r - s;
+                r = h[m + 2][m + 1];
+                s = p.abs() + q.abs() + r.abs();
+                p /= s;
+                q /= s;
+                r /= s;
+                if m == l {
+                    break;
+                }
+                if h[m][m - 1].abs() * (q.abs() + r.abs())
+                    < EPSILON * (p.abs() * (h[m - 1][m - 1].abs() + z.abs() + h[m + 1][m + 1].abs()))
+                {
+                    break;
+                }
+                m -= 1;
+            }
+
+            for i in m + 2..=nu {
+                h[i][i - 2] = 0.0;
+                if i > m + 2 {
+                    h[i][i - 3] = 0.0;
+                }
+            }
+
+            // double QR step involving rows l..n and columns m..n
+            for k in m..nu {
+                let not_last = k != nu - 1;
+                if k != m {
+                    p = h[k][k - 1];
+                    q = h[k + 1][k - 1];
+                    r = if not_last { h[k + 2][k - 1] } else { 0.0 };
+                    x = p.abs() + q.abs() + r.abs();
+                    if x == 0.0 {
+                        continue;
+                    }
+                    p /= x;
+                    q /= x;
+                    r /= x;
+                }
+                s = (p * p + q * q + r * r).sqrt();
+                if p < 0.0 {
+                    s = -s;
+                }
+                if s == 0.0 {
+                    continue;
+                }
+                if k != m {
+                    h[k][k - 1] = -s * x;
+                } else if l != m {
+                    h[k][k - 1] = -h[k][k - 1];
+                }
+                p += s;
+                x = p / s;
+                y = q / s;
+                z = r / s;
+                q /= p;
+                r /= p;
+
+                for j in k..nn {
+                    p = h[k][j] + q * h[k + 1][j];
+                    if not_last {
+                        p += r * h[k + 2][j];
+                        h[k + 2][j] -= p * z;
+                    }
+                    h[k][j] -= p * x;
+                    h[k + 1][j] -= p * y;
+                }
+                for i in 0..=nu.min(k + 3) {
+                    p = x * h[i][k] + y * h[i][k + 1];
+                    if not_last {
+                        p += z * h[i][k + 2];
+                        h[i][k + 2] -= p * r;
+                    }
+                    h[i][k] -= p;
+                    h[i][k + 1] -= p * q;
+                }
+                for i in 0..=high {
+                    p = x * v[i][k] + y * v[i][k + 1];
+                    if not_last {
+                        p += z * v[i][k + 2];
+                        v[i][k + 2] -= p * r;
+                    }
+                    v[i][k] -= p;
+                    v[i][k + 1] -= p * q;
+                }
+            }
+        }
+    }
+
+    if norm == 0.0 {
+        return Ok((d, e));
+    }
+
+    // back substitute to find the vectors of the upper triangular form
+    for n in (0..nn).rev() {
+        p = d[n];
+        q = e[n];
+
+        if q == 0.0 {
+            // real vector
+            let mut l = n;
+            h[n][n] = 1.0;
+            for i in (0..n).rev() {
+                w = h[i][i] - p;
+                r = (l..=n).map(|j| h[i][j] * h[j][n]).sum();
+                if e[i] < 0.0 {
+                    z = w;
+                    s = r;
+                } else {
+                    l = i;
+                    if e[i] == 0.0 {
+                        h[i][n] = if w != 0.0 { -r / w } else { -r / (EPSILON * norm) };
+                    } else {
+                        x = h[i][i + 1];
+                        y = h[i + 1][i];
+                        q = (d[i] - p) * (d[i] - p) + e[i] * e[i];
+                        t = (x * s - z * r) / q;
+                        h[i][n] = t;
+                        h[i + 1][n] = if x.abs() > z.abs() { (-r - w * t) / x } else { (-s - y * t) / z };
+                    }
+                    // overflow control
+                    t = h[i][n].abs();
+                    if (EPSILON * t) * t > 1.0 {
+                        for j in i..=n {
+                            h[j][n] /= t;
+                        }
+                    }
+                }
+            }
+        } else if q < 0.0 {
+            // complex vector; the last component is chosen imaginary so the matrix is triangular
+            let mut l = n - 1;
+            if h[n][n - 1].abs() > h[n - 1][n].abs() {
+                h[n - 1][n - 1] = q / h[n][n - 1];
+                h[n - 1][n] = -(h[n][n] - p) / h[n][n - 1];
+            } else {
+                let (cr, ci) = cdiv(0.0, -h[n - 1][n], h[n - 1][n - 1] - p, q);
+                h[n - 1][n - 1] = cr;
+                h[n - 1][n] = ci;
+            }
+            h[n][n - 1] = 0.0;
+            h[n][n] = 1.0;
+            for i in (0..n - 1).rev() {
+                let ra: f64 = (l..=n).map(|j| h[i][j] * h[j][n - 1]).sum();
+                let sa: f64 = (l..=n).map(|j| h[i][j] * h[j][n]).sum();
+                w = h[i][i] - p;
+
+                if e[i] < 0.0 {
+                    z = w;
+                    r = ra;
+                    s = sa;
+                } else {
+                    l = i;
+                    if e[i] == 0.0 {
+                        let (cr, ci) = cdiv(-ra, -sa, w, q);
+                        h[i][n - 1] = cr;
+                        h[i][n] = ci;
+                    } else {
+                        // solve complex equations
+                        x = h[i][i + 1];
+                        y = h[i + 1][i];
+                        let mut vr = (d[i] - p) * (d[i] - p) + e[i] * e[i] - q * q;
+                        let vi = (d[i] - p) * 2.0 * q;
+                        if vr == 0.0 && vi == 0.0 {
+                            vr = EPSILON * norm * (w.abs() + q.abs() + x.abs() + y.abs() + z.abs());
+                        }
+                        let (cr, ci) = cdiv(x * r - z * ra + q * sa, x * s - z * sa - q * ra, vr, vi);
+                        h[i][n - 1] = cr;
+                        h[i][n] = ci;
+                        if x.abs() > z.abs() + q.abs() {
+                            h[i + 1][n - 1] = (-ra - w * h[i][n - 1] + q * h[i][n]) / x;
+                            h[i + 1][n] = (-sa - w * h[i][n] - q * h[i][n - 1]) / x;
+                        } else {
+                            let (cr, ci) = cdiv(-r - y * h[i][n - 1], -s - y * h[i][n], z, q);
+                            h[i + 1][n - 1] = cr;
+                            h[i + 1][n] = ci;
+                        }
+                    }
+                    // overflow control
+                    t = h[i][n - 1].abs().max(h[i][n].abs());
+                    if (EPSILON * t) * t > 1.0 {
+                        for j in i..=n {
+                            h[j][n - 1] /= t;
+                            h[j][n] /= t;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // back transformation to get the eigenvectors of the original matrix
+    for j in (0..nn).rev() {
+        for i in 0..=high {
+            z = (0..=j.min(high)).map(|k| v[i][k] * h[k][j]).sum();
+            v[i][j] = z;
+        }
+    }
+    Ok((d, e))
+}
+
+// Singular value decomposition by one-sided Jacobi rotations: A = U S V^T with the
+// singular values in descending order. U is m x k, S is k x k and V is n x k, k = min(m, n).
+pub(crate) fn svd(a: &[Vec<f64>]) -> Result<(Matrix, Matrix, Matrix), String> {
+    let (m, n) = shape(a);
+    if m == 0 || n == 0 {
+        return Err("Matrix is empty".to_string());
+    }
+    if m < n {
+        // work on the transpose so there are at least as many rows as columns
+        let (u, s, v) = svd(&transpose(a))?;
+        return Ok((v, s, u));
+    }
+
+    let mut u = a.to_vec();
+    let mut v = identity(n);
+    for _ in 0..MAX_SWEEPS {
+        let mut rotated = false;
+        for p in 0..n {
+            for q in p + 1..n {
+                let alpha: f64 = (0..m).map(|i| u[i][p] * u[i][p]).sum();
+                let beta: f64 = (0..m).map(|i| u[i][q] * u[i][q]).sum();
+                let gamma: f64 = (0..m).map(|i| u[i][p] * u[i][q]).sum();
+                if gamma == 0.0 || gamma.abs() <= EPSILON * (alpha * beta).sqrt() {
+                    continue;
+                }
+                rotated = true;
+                let zeta = (beta - alpha) / (2.0 * gamma);
+                let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                let c = 1.0 / (1.0 + t * t).sqrt();
+                let s = c * t;
+                for i in 0..m {
+                    let (up, uq) = (u[i][p], u[i][q]);
+                    u[i][p] = c * up - s * uq;
+                    u[i][q] = s * up + c * uq;
+                }
+                for i in 0..n {
+                    let (vp, vq) = (v[i][p], v[i][q]);
+                    v[i][p] = c * vp - s * vq;
+                    v[i][q] = s * vp + c * vq;
+                }
+            }
+        }
+        if !rotated {
+            break;
+        }
+    }
+
+    // the column norms are the singular values; normalizing the columns gives U
+    let mut sigma: Vec<f64> = (0..n).map(|j| (0..m).map(|i| u[i][j] * u[i][j]).sum::<f64>().sqrt()).collect();
+    for j in 0..n {
+        if sigma[j] > 0.0 {
+            for i in 0..m {
+                u[i][j] /= sigma[j];
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| sigma[j].total_cmp(&sigma[i]));
+    let u_sorted = (0..m).map(|i| order.iter().map(|&j| u[i][j]).collect()).collect();
+    let v_sorted = (0..n).map(|i| order.iter().map(|&j| v[i][j]).collect()).collect();
+    sigma = order.iter().map(|&j| sigma[j]).collect();
+    let s = (0..n).map(|i| (0..n).map(|j| if i == j { sigma[i] } else { 0.0 }).collect()).collect();
+    Ok((u_sorted, s, v_sorted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::linalg::multiply;
+
+    const TOLERANCE: f64 = 1e-9;
+
+    fn assert_close(actual: &[Vec<f64>], expected: &[Vec<f64>]) {
+        assert_eq!(shape(actual), shape(expected), "shape of {:?}", actual);
+        for (actual_row, expected_row) in actual.iter().zip(expected) {
+            for (x, y) in actual_row.iter().zip(expected_row) {
+                assert!((x - y).abs() <= TOLERANCE, "{:?} != {:?}", actual, expected);
+            }
+        }
+    }
+
+    fn product(a: &[Vec<f64>], b: &[Vec<f64>]) -> Matrix {
+        multiply(a, b).unwrap()
+    }
+
+    fn square() -> Matrix {
+        vec![vec![2.0, 1.0, 1.0], vec![4.0, -6.0, 0.0], vec![-2.0, 7.0, 2.0]]
+    }
+
+    fn singular() -> Matrix {
+        vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0], vec![7.0, 8.0, 9.0]]
+    }
+
+    fn tall() -> Matrix {
+        vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]]
+    }
+
+    #[test]
+    fn lu_reconstructs_pa() {
+        for a in [square(), singular(), vec![vec![0.0, 1.0], vec![1.0, 0.0]]] {
+            let (l, u, p) = lu(&a).unwrap();
+            assert_close(&product(&p, &a), &product(&l, &u));
+            for i in 0..a.len() {
+                assert_eq!(l[i][i], 1.0);
+                assert!((i + 1..a.len()).all(|j| l[i][j] == 0.0 && u[j][i] == 0.0));
+            }
+        }
+    }
+
+    #[test]
+    fn qr_reconstructs_a_with_orthogonal_q() {
+        for a in [square(), singular(), tall()] {
+            let (q, r) = qr(&a).unwrap();
+            assert_close(&product(&q, &r), &a);
+            assert_close(&product(&transpose(&q), &q), &identity(q.len()));
+            for i in 0..r.len() {
+                assert!((0..i.min(r[i].len())).all(|j| r[i][j].abs() <= TOLERANCE));
+            }
+        }
+    }
+
+    #[test]
+    fn cholesky_reconstructs_a() {
+        let a = vec![vec![4.0, 12.0, -16.0], vec![12.0, 37.0, -43.0], vec![-16.0, -43.0, 98.0]];
+        let l = cholesky(&a).unwrap();
+        assert_close(&l, &[vec![2.0, 0.0, 0.0], vec![6.0, 1.0, 0.0], vec![-8.0, 5.0, 3.0]]);
+        assert_close(&product(&l, &transpose(&l)), &a);
+        assert!(cholesky(&singular()).is_err());
+        assert!(cholesky(&[vec![1.0, 2.0], vec![2.0, 1.0]]).is_err());
+    }
+
+    #[test]
+    fn symmetric_eigenvectors_satisfy_av_lambda_v() {
+        let a = vec![vec![2.0, -1.0, 0.0], vec![-1.0, 2.0, -1.0], vec![0.0, -1.0, 2.0]];
+        let (values, vectors) = eigen_symmetric(&a).unwrap();
+        let root2 = 2f64.sqrt();
+        for (value, expected) in values.iter().zip([2.0 - root2, 2.0, 2.0 + root2]) {
+            assert!((value - expected).abs() <= TOLERANCE);
+        }
+        let lambda = (0..3).map(|i| (0..3).map(|j| if i == j { values[i] } else { 0.0 }).collect()).collect::<Matrix>();
+        assert_close(&product(&a, &vectors), &product(&vectors, &lambda));
+        assert!(eigen_symmetric(&square()).is_err());
+    }
+
+    #[test]
+    fn general_eigenvectors_satisfy_av_lambda_v() {
+        // real eigenvalues 1, 2 and 3 of a non-symmetric matrix
+        let a = vec![vec![1.0, 2.0, 3.0], vec![0.0, 2.0, 4.0], vec![0.0, 0.0, 3.0]];
+        let (re, im, v) = eigen_general(&a).unwrap();
+        let mut sorted = re.clone();
+        sorted.sort_by(f64::total_cmp);
+        assert_close(&[sorted], &[vec![1.0, 2.0, 3.0]]);
+        assert!(im.iter().all(|x| x.abs() <= TOLERANCE));
+        let lambda = (0..3).map(|i| (0..3).map(|j| if i == j { re[i] } else { 0.0 }).collect()).collect::<Matrix>();
+        assert_close(&product(&a, &v), &product(&v, &lambda));
+
+        // a rotation has the complex pair cos t ± i sin t
+        let (c, s) = (0.6, 0.8);
+        let a = vec![vec![c, -s], vec![s, c]];
+        let (re, im, v) = eigen_general(&a).unwrap();
+        assert_close(std::slice::from_ref(&re), &[vec![c, c]]);
+        assert_close(&[vec![im[0].abs(), im[1].abs()]], &[vec![s, s]]);
+        // with x = v[:,k] + i v[:,k+1] and lambda = re + i im, A x = lambda x
+        let k = if im[0] > 0.0 { 0 } else { 1 };
+        let (lr, li) = (re[k], im[k]);
+        for row in 0..2 {
+            let ax_re: f64 = (0..2).map(|j| a[row][j] * v[j][0]).sum();
+            let ax_im: f64 = (0..2).map(|j| a[row][j] * v[j][1]).sum();
+            assert!((ax_re - (lr * v[row][0] - li * v[row][1])).abs() <= TOLERANCE);
+            assert!((ax_im - (lr * v[row][1] + li * v[row][0])).abs() <= TOLERANCE);
+        }
+    }
+
+    #[test]
+    fn svd_reconstructs_a() {
+        for a in [square(), singular(), tall(), transpose(&tall())] {
+            let (u, s, v) = svd(&a).unwrap();
+            assert_close(&product(&product(&u, &s), &transpose(&v)), &a);
+            assert_close(&product(&transpose(&v), &v), &identity(v[0].len()));
+            assert!((1..s.len()).all(|i| s[i - 1][i - 1] >= s[i][i]));
+        }
+        let (_, s, _) = svd(&singular()).unwrap();
+        assert!(s[2][2].abs() <= TOLERANCE);
+    }
+}
@@ -0,0 +1,214 @@
+// Dense linear algebra on row-major matrices. These functions are pure and report
+// problems (shape mismatches, singular matrices) as errors rather than panicking.
+
+pub(crate) type Matrix = Vec<Vec<f64>>;
+
+// Pivots smaller than this (relative to the largest entry) are treated as zero
+const SINGULAR_TOLERANCE: f64 = 1e-12;
+
+// Largest number of rows or columns a typed size may ask for; a million cells is
+// already slow to work with, and much larger sizes exhaust memory
+pub(crate) const MAX_DIMENSION: usize = 1000;
+
+pub(crate) fn shape(a: &[Vec<f64>]) -> (usize, usize) {
+    (a.len(), a.first().map_or(0, |row| row.len()))
+}
+
+pub(crate) fn require_square(a: &[Vec<f64>]) -> Result<usize, String> {
+    let (rows, cols) = shape(a);
+    if rows == 0 {
+        return Err("Matrix is empty".to_string());
+    }
+    if rows != cols {
+        return Err(format!("Matrix must be square, got {}x{}", rows, cols));
+    }
+    Ok(rows)
+}
+
+// Rejects a row or column count of zero or above MAX_DIMENSION, before anything is allocated
+pub(crate) fn check_dimension(n: usize) -> Result<(), String> {
+    if n == 0 {
+        return Err("Size must be at least 1".to_string());
+    }
+    if n > MAX_DIMENSION {
+        return Err(format!("Size {} is larger than the limit of {}", n, MAX_DIMENSION));
+    }
+    Ok(())
+}
+
+pub(crate) fn identity(n: usize) -> Matrix {
+    (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect()
+}
+
+pub(crate) fn transpose(a: &[Vec<f64>]) -> Matrix {
+    let (rows, cols) = shape(a);
+    // an array without elements stays a single empty row
+    if cols == 0 {
+        return vec![Vec::new()];
+    }
+    (0..cols).map(|j| (0..rows).map(|i| a[i][j]).collect()).collect()
+}
+
+pub(crate) fn multiply(a: &[Vec<f64>], b: &[Vec<f64>]) -> Result<Matrix, String> {
+    let (a_rows, a_cols) = shape(a);
+    let (b_rows, b_cols) = shape(b);
+    if a_rows == 0 || b_rows == 0 {
+        return Err("Cannot multiply empty arrays".to_string());
+    }
+    if a_cols != b_rows {
+        return Err(format!(
+            "Incompatible array dimensions for multiplication: {}x{} * {}x{}",
+            a_rows, a_cols, b_rows, b_cols
+        ));
+    }
+    Ok(a.iter()
+        .map(|row_a| {
+            (0..b_cols)
+                .map(|j| row_a.iter().zip(b.iter()).map(|(x, row_b)| x * row_b[j]).sum())
+                .collect()
+        })
+        .collect())
+}
+
+fn max_abs(a: &[Vec<f64>]) -> f64 {
+    a.iter().flatten().fold(0.0, |acc: f64, x| acc.max(x.abs()))
+}
+
+// Shared by det and solve, so a matrix solve rejects as singular has a zero determinant
+fn pivot_tolerance(a: &[Vec<f64>]) -> f64 {
+    SINGULAR_TOLERANCE * max_abs(a)
+}
+
+pub(crate) fn trace(a: &[Vec<f64>]) -> Result<f64, String> {
+    let n = require_square(a)?;
+    Ok((0..n).map(|i| a[i][i]).sum())
+}
+
+// Gaussian elimination with partial pivoting; the determinant is the signed product of the pivots
+pub(crate) fn determinant(a: &[Vec<f64>]) -> Result<f64, String> {
+    let n = require_square(a)?;
+    let tolerance = pivot_tolerance(a);
+    let mut m = a.to_vec();
+    let mut det = 1.0;
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| m[i][col].abs().total_cmp(&m[j][col].abs()))
+            .unwrap();
+        if m[pivot][col].abs() <= tolerance {
+            return Ok(0.0);
+        }
+        if pivot != col {
+            m.swap(pivot, col);
+            det = -det;
+        }
+        det *= m[col][col];
+        for row in col + 1..n {
+            let factor = m[row][col] / m[col][col];
+            for k in col..n {
+                m[row][k] -= factor * m[col][k];
+            }
+        }
+    }
+    Ok(det)
+}
+
+// Number of linearly independent rows, from the row echelon form
+pub(crate) fn rank(a: &[Vec<f64>]) -> usize {
+    let (rows, cols) = shape(a);
+    let mut m = a.to_vec();
+    let tolerance = SINGULAR_TOLERANCE * max_abs(a).max(1.0) * rows.max(cols) as f64;
+    let mut rank = 0;
+    for col in 0..cols {
+        if rank == rows {
+            break;
+        }
+        let pivot = (rank..rows)
+            .max_by(|&i, &j| m[i][col].abs().total_cmp(&m[j][col].abs()))
+            .unwrap();
+        if m[pivot][col].abs() <= tolerance {
+            continue;
+        }
+        m.swap(pivot, rank);
+        for row in rank + 1..rows {
+            let factor = m[row][col] / m[rank][col];
+            for k in col..cols {
+                m[row][k] -= factor * m[rank][k];
+            }
+        }
+        rank += 1;
+    }
+    rank
+}
+
+// Solves A X = B for X by Gauss-Jordan elimination with partial pivoting.
+// B may have any number of columns; the inverse is the solution for B = I.
+pub(crate) fn solve(a: &[Vec<f64>], b: &[Vec<f64>]) -> Result<Matrix, String> {
+    let n = require_square(a)?;
+    let (b_rows, b_cols) = shape(b);
+    if b_rows != n {
+        return Err(format!(
+            "Incompatible dimensions for solve: {}x{} matrix with {}x{} right-hand side",
+            n, n, b_rows, b_cols
+        ));
+    }
+    let tolerance = pivot_tolerance(a);
+    let mut m = a.to_vec();
+    let mut x = b.to_vec();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| m[i][col].abs().total_cmp(&m[j][col].abs()))
+            .unwrap();
+        if m[pivot][col].abs() <= tolerance {
+            return Err("Matrix is singular".to_string());
+        }
+        m.swap(pivot, col);
+        x.swap(pivot, col);
+
+        let pivot_value = m[col][col];
+        for k in 0..n {
+            m[col][k] /= pivot_value;
+        }
+        for k in 0..b_cols {
+            x[col][k] /= pivot_value;
+        }
+        for row in 0..n {
+            if row == col {
+                continue;
+            }
+            let factor = m[row][col];
+            if factor == 0.0 {
+                continue;
+            }
+            for k in 0..n {
+                m[row][k] -= factor * m[col][k];
+            }
+            for k in 0..b_cols {
+                x[row][k] -= factor * x[col][k];
+            }
+        }
+    }
+    Ok(x)
+}
+
+pub(crate) fn inverse(a: &[Vec<f64>]) -> Result<Matrix, String> {
+    let n = require_square(a)?;
+    solve(a, &identity(n))
+}
+
+// Integer matrix power by repeated squaring; negative powers use the inverse
+pub(crate) fn power(a: &[Vec<f64>], exponent: i64) -> Result<Matrix, String> {
+    let n = require_square(a)?;
+    let mut base = if exponent < 0 { inverse(a)? } else { a.to_vec() };
+    let mut remaining = exponent.unsigned_abs();
+    let mut result = identity(n);
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            result = multiply(&result, &base)?;
+        }
+        remaining >>= 1;
+        if remaining > 0 {
+            base = multiply(&base, &base)?;
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,13 @@
+// numeric code reads closer to the textbook algorithms with index loops
+#![allow(clippy::needless_range_loop)]
+
+pub(crate) mod linalg;
+pub(crate) mod decomp;
+pub(crate) mod complex;
+pub(crate) mod bigint;
+pub(crate) mod rational;
+pub(crate) mod word;
+pub(crate) mod units;
+pub(crate) mod stats;
+pub(crate) mod constants;
+pub(crate) mod datetime;
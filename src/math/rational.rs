@@ -0,0 +1,178 @@
+// Exact fractions of arbitrary-precision integers, always kept in lowest terms
+
+use std::cmp::Ordering;
+
+use crate::math::bigint::BigInt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Rational {
+    numerator: BigInt,
+    // always positive
+    denominator: BigInt,
+}
+
+impl Rational {
+    pub(crate) fn new(numerator: BigInt, denominator: BigInt) -> Result<Rational, String> {
+        if denominator.is_zero() {
+            return Err("Division by zero".to_string());
+        }
+        let divisor = numerator.gcd(&denominator);
+        let (mut numerator, _) = numerator.div_rem(&divisor)?;
+        let (mut denominator, _) = denominator.div_rem(&divisor)?;
+        if denominator.is_negative() {
+            numerator = -&numerator;
+            denominator = -&denominator;
+        }
+        Ok(Rational { numerator, denominator })
+    }
+
+    pub(crate) fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub(crate) fn denominator(&self) -> &BigInt {
+        &self.denominator
+    }
+
+    pub(crate) fn is_integer(&self) -> bool {
+        self.denominator == BigInt::one()
+    }
+
+    // Parses "p/q" with integer p and q
+    pub(crate) fn parse(text: &str) -> Option<Result<Rational, String>> {
+        let (numerator, denominator) = text.split_once('/')?;
+        let numerator = BigInt::parse(numerator)?;
+        let denominator = BigInt::parse(denominator)?;
+        Some(Rational::new(numerator, denominator))
+    }
+
+    // The exact value of the shortest decimal that prints as `value`, so 0.1 becomes 1/10
+    pub(crate) fn from_f64(value: f64) -> Result<Rational, String> {
+        if !value.is_finite() {
+            return Err(format!("{} has no exact value", value));
+        }
+        // Display for f64 never uses exponent notation
+        let text = format!("{}", value);
+        let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+        let numerator = BigInt::parse(&format!("{}{}", whole, fraction)).unwrap();
+        let denominator = BigInt::from(10i64).pow(fraction.len() as u32);
+        Rational::new(numerator, denominator)
+    }
+
+    pub(crate) fn to_f64(&self) -> f64 {
+        let (numerator, denominator) = (self.numerator.to_f64(), self.denominator.to_f64());
+        if numerator.is_finite() && denominator.is_finite() {
+            return numerator / denominator;
+        }
+        // too large for a float on their own: drop low bits from both before dividing
+        let excess = self.numerator.bit_length().max(self.denominator.bit_length()) - 1000;
+        let scale = BigInt::from(2i64).pow(excess as u32);
+        let (numerator, _) = self.numerator.div_rem(&scale).unwrap();
+        let (denominator, _) = self.denominator.div_rem(&scale).unwrap();
+        numerator.to_f64() / denominator.to_f64()
+    }
+
+    pub(crate) fn abs(&self) -> Rational {
+        Rational { numerator: self.numerator.abs(), denominator: self.denominator.clone() }
+    }
+
+    pub(crate) fn checked_div(&self, rhs: &Rational) -> Result<Rational, String> {
+        Rational::new(&self.numerator * &rhs.denominator, &self.denominator * &rhs.numerator)
+    }
+
+    // Largest integer not above the value
+    pub(crate) fn floor(&self) -> BigInt {
+        let (quotient, remainder) = self.numerator.div_rem(&self.denominator).unwrap();
+        if remainder.is_negative() {
+            &quotient - &BigInt::one()
+        } else {
+            quotient
+        }
+    }
+
+    pub(crate) fn ceil(&self) -> BigInt {
+        -&Rational { numerator: -&self.numerator, denominator: self.denominator.clone() }.floor()
+    }
+
+    // Halves round away from zero
+    pub(crate) fn round(&self) -> BigInt {
+        let half = Rational { numerator: BigInt::one(), denominator: BigInt::from(2i64) };
+        if self.numerator.is_negative() {
+            -&(&self.abs() + &half).floor()
+        } else {
+            (self + &half).floor()
+        }
+    }
+
+    // Remainder of floored division, taking the sign of the divisor
+    pub(crate) fn modulo(&self, rhs: &Rational) -> Result<Rational, String> {
+        let quotient = Rational::from(self.checked_div(rhs)?.floor());
+        Ok(self - &(rhs * &quotient))
+    }
+
+    // Integer powers only; a negative power inverts the fraction
+    pub(crate) fn pow(&self, exponent: i64) -> Result<Rational, String> {
+        let magnitude = u32::try_from(exponent.unsigned_abs()).map_err(|_| "Exponent too large".to_string())?;
+        let numerator = self.numerator.pow(magnitude);
+        let denominator = self.denominator.pow(magnitude);
+        if exponent < 0 {
+            Rational::new(denominator, numerator)
+        } else {
+            Rational::new(numerator, denominator)
+        }
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(value: BigInt) -> Rational {
+        Rational { numerator: value, denominator: BigInt::one() }
+    }
+}
+
+impl std::fmt::Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Rational) -> Ordering {
+        (&self.numerator * &other.denominator).cmp(&(&other.numerator * &self.denominator))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::ops::Add for &Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: &Rational) -> Rational {
+        let numerator = &(&self.numerator * &rhs.denominator) + &(&rhs.numerator * &self.denominator);
+        Rational::new(numerator, &self.denominator * &rhs.denominator).unwrap()
+    }
+}
+
+impl std::ops::Sub for &Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: &Rational) -> Rational {
+        let numerator = &(&self.numerator * &rhs.denominator) - &(&rhs.numerator * &self.denominator);
+        Rational::new(numerator, &self.denominator * &rhs.denominator).unwrap()
+    }
+}
+
+impl std::ops::Mul for &Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: &Rational) -> Rational {
+        Rational::new(&self.numerator * &rhs.numerator, &self.denominator * &rhs.denominator).unwrap()
+    }
+}
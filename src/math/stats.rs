@@ -0,0 +1,193 @@
+// Descriptive statistics and regression on samples of floats. Every function
+// needs at least one value (two for sample variance) and reports that as an error.
+
+use crate::math::linalg::{shape, Matrix};
+
+fn require_values(values: &[f64], needed: usize) -> Result<(), String> {
+    if values.len() < needed {
+        return Err(format!("Need at least {} value{}, got {}", needed, if needed == 1 { "" } else { "s" }, values.len()));
+    }
+    Ok(())
+}
+
+fn sorted(values: &[f64]) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted
+}
+
+pub(crate) fn mean(values: &[f64]) -> Result<f64, String> {
+    require_values(values, 1)?;
+    Ok(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+pub(crate) fn median(values: &[f64]) -> Result<f64, String> {
+    percentile(values, 50.0)
+}
+
+// The most frequent value; ties go to the smallest
+pub(crate) fn mode(values: &[f64]) -> Result<f64, String> {
+    require_values(values, 1)?;
+    let sorted = sorted(values);
+    let (mut best, mut best_count) = (sorted[0], 0);
+    let mut start = 0;
+    while start < sorted.len() {
+        let end = start + sorted[start..].iter().take_while(|&&x| x == sorted[start]).count().max(1);
+        if end - start > best_count {
+            best = sorted[start];
+            best_count = end - start;
+        }
+        start = end;
+    }
+    Ok(best)
+}
+
+// Sample variance divides by n - 1, population variance by n
+pub(crate) fn variance(values: &[f64], sample: bool) -> Result<f64, String> {
+    require_values(values, if sample { 2 } else { 1 })?;
+    let mean = mean(values)?;
+    let squares: f64 = values.iter().map(|x| (x - mean).powi(2)).sum();
+    Ok(squares / (values.len() - usize::from(sample)) as f64)
+}
+
+pub(crate) fn std_dev(values: &[f64], sample: bool) -> Result<f64, String> {
+    Ok(variance(values, sample)?.sqrt())
+}
+
+pub(crate) fn min(values: &[f64]) -> Result<f64, String> {
+    require_values(values, 1)?;
+    Ok(values.iter().copied().fold(f64::INFINITY, f64::min))
+}
+
+pub(crate) fn max(values: &[f64]) -> Result<f64, String> {
+    require_values(values, 1)?;
+    Ok(values.iter().copied().fold(f64::NEG_INFINITY, f64::max))
+}
+
+// Linear interpolation between the closest ranks, p from 0 to 100
+pub(crate) fn percentile(values: &[f64], p: f64) -> Result<f64, String> {
+    require_values(values, 1)?;
+    if !(0.0..=100.0).contains(&p) {
+        return Err(format!("Percentile must be between 0 and 100, got {}", p));
+    }
+    let sorted = sorted(values);
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Ok(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+// Sample covariance matrix of the columns (each column is a variable)
+pub(crate) fn covariance(data: &[Vec<f64>]) -> Result<Matrix, String> {
+    let (rows, cols) = shape(data);
+    if rows < 2 {
+        return Err(format!("Need at least 2 observations, got {}", rows));
+    }
+    let means: Vec<f64> = (0..cols).map(|j| data.iter().map(|row| row[j]).sum::<f64>() / rows as f64).collect();
+    let mut result = vec![vec![0.0; cols]; cols];
+    for a in 0..cols {
+        for b in a..cols {
+            let sum: f64 = data.iter().map(|row| (row[a] - means[a]) * (row[b] - means[b])).sum();
+            result[a][b] = sum / (rows - 1) as f64;
+            result[b][a] = result[a][b];
+        }
+    }
+    Ok(result)
+}
+
+// Pearson correlation matrix of the columns. A constant column has no correlation
+// with anything, so it is an error rather than a row of NaN.
+pub(crate) fn correlation(data: &[Vec<f64>]) -> Result<Matrix, String> {
+    let covariance = covariance(data)?;
+    let n = covariance.len();
+    if let Some(column) = (0..n).find(|&i| covariance[i][i] == 0.0) {
+        return Err(format!("Column {} is constant, so its correlation is undefined", column + 1));
+    }
+    let scale: Vec<f64> = (0..n).map(|i| covariance[i][i].sqrt()).collect();
+    Ok((0..n).map(|i| (0..n).map(|j| covariance[i][j] / (scale[i] * scale[j])).collect()).collect())
+}
+
+pub(crate) struct LinearFit {
+    pub slope: f64,
+    pub intercept: f64,
+    pub r_squared: f64,
+}
+
+// Least squares line through (x, y) points
+pub(crate) fn linear_regression(x: &[f64], y: &[f64]) -> Result<LinearFit, String> {
+    if x.len() != y.len() {
+        return Err(format!("x and y must have the same length, got {} and {}", x.len(), y.len()));
+    }
+    require_values(x, 2)?;
+    let (mean_x, mean_y) = (mean(x)?, mean(y)?);
+    let sxx: f64 = x.iter().map(|a| (a - mean_x).powi(2)).sum();
+    let syy: f64 = y.iter().map(|b| (b - mean_y).powi(2)).sum();
+    let sxy: f64 = x.iter().zip(y).map(|(a, b)| (a - mean_x) * (b - mean_y)).sum();
+    if sxx == 0.0 {
+        return Err("x values are all equal".to_string());
+    }
+    let slope = sxy / sxx;
+    // a horizontal line through constant data fits it exactly
+    let r_squared = if syy == 0.0 { 1.0 } else { sxy * sxy / (sxx * syy) };
+    Ok(LinearFit { slope, intercept: mean_y - slope * mean_x, r_squared })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: [f64; 8] = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn descriptive_statistics() {
+        assert_eq!(mean(&SAMPLE), Ok(5.0));
+        assert_eq!(median(&SAMPLE), Ok(4.5));
+        assert_eq!(mode(&SAMPLE), Ok(4.0));
+        assert_eq!(variance(&SAMPLE, false), Ok(4.0));
+        assert_eq!(std_dev(&SAMPLE, false), Ok(2.0));
+        assert!(close(variance(&SAMPLE, true).unwrap(), 32.0 / 7.0));
+        assert_eq!(min(&SAMPLE), Ok(2.0));
+        assert_eq!(max(&SAMPLE), Ok(9.0));
+        assert_eq!(percentile(&SAMPLE, 0.0), Ok(2.0));
+        assert_eq!(percentile(&SAMPLE, 100.0), Ok(9.0));
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0, 5.0], 90.0), Ok(4.6));
+    }
+
+    #[test]
+    fn too_few_values_are_errors() {
+        assert!(mean(&[]).is_err());
+        assert!(variance(&[1.0], true).is_err());
+        assert_eq!(variance(&[1.0], false), Ok(0.0));
+        assert!(percentile(&SAMPLE, 101.0).is_err());
+    }
+
+    #[test]
+    fn covariance_and_correlation_of_columns() {
+        let data = vec![vec![1.0, 2.0, 3.0], vec![2.0, 4.0, 1.0], vec![3.0, 6.0, 2.0]];
+        let cov = covariance(&data).unwrap();
+        assert_eq!(cov[0], vec![1.0, 2.0, -0.5]);
+        assert_eq!(cov[1][1], 4.0);
+        let corr = correlation(&data).unwrap();
+        assert!(close(corr[0][1], 1.0));
+        assert!(close(corr[0][2], -0.5));
+        assert!(close(corr[2][2], 1.0));
+        assert_eq!(corr[1][0], corr[0][1]);
+        assert!(correlation(&[vec![1.0, 5.0], vec![2.0, 5.0]]).unwrap_err().contains("Column 2"));
+        assert!(covariance(&[vec![1.0, 2.0]]).is_err());
+    }
+
+    #[test]
+    fn linear_regression_fits_a_line() {
+        let fit = linear_regression(&[1.0, 2.0, 3.0, 4.0], &[3.0, 5.0, 7.0, 9.0]).unwrap();
+        assert_eq!((fit.slope, fit.intercept, fit.r_squared), (2.0, 1.0, 1.0));
+        let fit = linear_regression(&[0.0, 1.0, 2.0], &[1.0, 0.0, 2.0]).unwrap();
+        assert!(close(fit.slope, 0.5));
+        assert!(close(fit.intercept, 0.5));
+        assert!(close(fit.r_squared, 0.25));
+        assert!(linear_regression(&[1.0, 1.0], &[2.0, 3.0]).is_err());
+        assert!(linear_regression(&[1.0, 2.0], &[2.0]).is_err());
+    }
+}
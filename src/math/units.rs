@@ -0,0 +1,310 @@
+// Physical quantities: a float value with a unit made of named unit factors.
+// Units are kept as the user wrote them (km/h stays km/h) and only reduced to SI
+// when converting or when they cancel out completely. The factors are kept sorted
+// by symbol, so equal units compare equal however they were written.
+
+// Exponents of the SI base dimensions, in the order of BASE_SYMBOLS
+pub(crate) type Dimension = [i32; 7];
+
+const BASE_SYMBOLS: [&str; 7] = ["kg", "m", "s", "A", "K", "mol", "cd"];
+const DIMENSIONLESS: Dimension = [0; 7];
+// Dimensionless, but worth keeping on a value: 90_deg should not become 1.5708
+const ANGLE_SYMBOLS: [&str; 2] = ["rad", "deg"];
+
+struct UnitDef {
+    symbol: &'static str,
+    // multiply by this to get SI base units
+    factor: f64,
+    dimension: Dimension,
+    // whether SI prefixes (k, m, µ, ...) may be attached
+    prefixable: bool,
+}
+
+const fn unit(symbol: &'static str, factor: f64, dimension: Dimension, prefixable: bool) -> UnitDef {
+    UnitDef { symbol, factor, dimension, prefixable }
+}
+
+//                                         kg  m  s  A  K mol cd
+const UNITS: &[UnitDef] = &[
+    // SI base and derived units
+    unit("m", 1.0, [0, 1, 0, 0, 0, 0, 0], true),
+    unit("g", 1e-3, [1, 0, 0, 0, 0, 0, 0], true),
+    unit("s", 1.0, [0, 0, 1, 0, 0, 0, 0], true),
+    unit("A", 1.0, [0, 0, 0, 1, 0, 0, 0], true),
+    unit("K", 1.0, [0, 0, 0, 0, 1, 0, 0], true),
+    unit("mol", 1.0, [0, 0, 0, 0, 0, 1, 0], true),
+    unit("cd", 1.0, [0, 0, 0, 0, 0, 0, 1], true),
+    unit("N", 1.0, [1, 1, -2, 0, 0, 0, 0], true),
+    unit("J", 1.0, [1, 2, -2, 0, 0, 0, 0], true),
+    unit("W", 1.0, [1, 2, -3, 0, 0, 0, 0], true),
+    unit("Pa", 1.0, [1, -1, -2, 0, 0, 0, 0], true),
+    unit("Hz", 1.0, [0, 0, -1, 0, 0, 0, 0], true),
+    unit("C", 1.0, [0, 0, 1, 1, 0, 0, 0], true),
+    unit("V", 1.0, [1, 2, -3, -1, 0, 0, 0], true),
+    unit("ohm", 1.0, [1, 2, -3, -2, 0, 0, 0], true),
+    unit("Ω", 1.0, [1, 2, -3, -2, 0, 0, 0], true),
+    unit("S", 1.0, [-1, -2, 3, 2, 0, 0, 0], true),
+    unit("F", 1.0, [-1, -2, 4, 2, 0, 0, 0], true),
+    unit("H", 1.0, [1, 2, -2, -2, 0, 0, 0], true),
+    unit("T", 1.0, [1, 0, -2, -1, 0, 0, 0], true),
+    unit("Wb", 1.0, [1, 2, -2, -1, 0, 0, 0], true),
+    unit("L", 1e-3, [0, 3, 0, 0, 0, 0, 0], true),
+    unit("eV", 1.602176634e-19, [1, 2, -2, 0, 0, 0, 0], true),
+    unit("bar", 1e5, [1, -1, -2, 0, 0, 0, 0], true),
+    unit("cal", 4.184, [1, 2, -2, 0, 0, 0, 0], true),
+    unit("t", 1e3, [1, 0, 0, 0, 0, 0, 0], false),
+    // time
+    unit("min", 60.0, [0, 0, 1, 0, 0, 0, 0], false),
+    unit("h", 3600.0, [0, 0, 1, 0, 0, 0, 0], false),
+    unit("d", 86400.0, [0, 0, 1, 0, 0, 0, 0], false),
+    unit("yr", 31557600.0, [0, 0, 1, 0, 0, 0, 0], false),
+    // imperial and US customary
+    unit("in", 0.0254, [0, 1, 0, 0, 0, 0, 0], false),
+    unit("ft", 0.3048, [0, 1, 0, 0, 0, 0, 0], false),
+    unit("yd", 0.9144, [0, 1, 0, 0, 0, 0, 0], false),
+    unit("mi", 1609.344, [0, 1, 0, 0, 0, 0, 0], false),
+    unit("nmi", 1852.0, [0, 1, 0, 0, 0, 0, 0], false),
+    unit("oz", 0.028349523125, [1, 0, 0, 0, 0, 0, 0], false),
+    unit("lb", 0.45359237, [1, 0, 0, 0, 0, 0, 0], false),
+    unit("lbf", 4.4482216152605, [1, 1, -2, 0, 0, 0, 0], false),
+    unit("psi", 6894.757293168361, [1, -1, -2, 0, 0, 0, 0], false),
+    unit("gal", 3.785411784e-3, [0, 3, 0, 0, 0, 0, 0], false),
+    unit("mph", 0.44704, [0, 1, -1, 0, 0, 0, 0], false),
+    unit("kn", 1852.0 / 3600.0, [0, 1, -1, 0, 0, 0, 0], false),
+    unit("hp", 745.6998715822702, [1, 2, -3, 0, 0, 0, 0], false),
+    unit("BTU", 1055.05585262, [1, 2, -2, 0, 0, 0, 0], false),
+    // other
+    unit("atm", 101325.0, [1, -1, -2, 0, 0, 0, 0], false),
+    unit("mmHg", 133.322387415, [1, -1, -2, 0, 0, 0, 0], false),
+    unit("rad", 1.0, DIMENSIONLESS, false),
+    unit("deg", std::f64::consts::PI / 180.0, DIMENSIONLESS, false),
+    unit("rpm", std::f64::consts::PI / 30.0, [0, 0, -1, 0, 0, 0, 0], false),
+];
+
+const PREFIXES: &[(&str, f64)] = &[
+    ("Y", 1e24), ("Z", 1e21), ("E", 1e18), ("P", 1e15), ("T", 1e12), ("G", 1e9),
+    ("M", 1e6), ("k", 1e3), ("h", 1e2), ("da", 1e1), ("d", 1e-1), ("c", 1e-2),
+    ("m", 1e-3), ("u", 1e-6), ("µ", 1e-6), ("n", 1e-9), ("p", 1e-12), ("f", 1e-15),
+    ("a", 1e-18), ("z", 1e-21), ("y", 1e-24),
+];
+
+// Resolves a possibly prefixed symbol ("km", "mA") to its SI factor and dimension.
+// Exact symbols win over prefixed readings, so "min" is minutes, not milli-inches.
+fn lookup(symbol: &str) -> Option<(f64, Dimension)> {
+    if let Some(def) = UNITS.iter().find(|def| def.symbol == symbol) {
+        return Some((def.factor, def.dimension));
+    }
+    PREFIXES.iter().find_map(|(prefix, scale)| {
+        let rest = symbol.strip_prefix(prefix)?;
+        let def = UNITS.iter().find(|def| def.symbol == rest && def.prefixable)?;
+        Some((scale * def.factor, def.dimension))
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Unit {
+    // (symbol, exponent) pairs sorted by symbol, with distinct symbols and non-zero exponents
+    factors: Vec<(String, i32)>,
+}
+
+impl Unit {
+    pub(crate) fn none() -> Unit {
+        Unit { factors: Vec::new() }
+    }
+
+    // Parses expressions like "m/s^2", "kg*m^2/s^2" or "1/s". Each "/" divides
+    // by the single factor that follows it.
+    pub(crate) fn parse(text: &str) -> Result<Unit, String> {
+        let mut unit = Unit::none();
+        let mut sign = 1;
+        let mut rest = text.trim();
+        while !rest.is_empty() {
+            let end = rest.find(['*', '/']).unwrap_or(rest.len());
+            let term = &rest[..end];
+            let (symbol, exponent) = match term.split_once('^') {
+                Some((symbol, exponent)) => (
+                    symbol,
+                    exponent.parse::<i32>().map_err(|_| format!("Invalid unit exponent: {}", exponent))?,
+                ),
+                None => (term, 1),
+            };
+            if symbol != "1" {
+                if lookup(symbol).is_none() {
+                    return Err(format!("Unknown unit: {}", symbol));
+                }
+                unit.add_factor(symbol, sign * exponent);
+            }
+            if end == rest.len() {
+                break;
+            }
+            sign = if rest[end..].starts_with('/') { -1 } else { 1 };
+            rest = &rest[end + 1..];
+        }
+        if text.trim().is_empty() {
+            return Err("Missing unit".to_string());
+        }
+        Ok(unit)
+    }
+
+    fn add_factor(&mut self, symbol: &str, exponent: i32) {
+        match self.factors.binary_search_by(|(existing, _)| existing.as_str().cmp(symbol)) {
+            Ok(index) => self.factors[index].1 += exponent,
+            Err(index) => self.factors.insert(index, (symbol.to_string(), exponent)),
+        }
+        self.factors.retain(|(_, exponent)| *exponent != 0);
+    }
+
+    // Whether an angle unit is among the factors
+    pub(crate) fn has_angle(&self) -> bool {
+        self.factors.iter().any(|(symbol, _)| ANGLE_SYMBOLS.contains(&symbol.as_str()))
+    }
+
+    // Factor to SI base units and the combined dimension
+    pub(crate) fn to_si(&self) -> (f64, Dimension) {
+        let mut factor = 1.0;
+        let mut dimension = DIMENSIONLESS;
+        for (symbol, exponent) in &self.factors {
+            let (unit_factor, unit_dimension) = lookup(symbol).unwrap();
+            factor *= unit_factor.powi(*exponent);
+            for (total, part) in dimension.iter_mut().zip(unit_dimension) {
+                *total += part * exponent;
+            }
+        }
+        (factor, dimension)
+    }
+
+    pub(crate) fn dimension(&self) -> Dimension {
+        self.to_si().1
+    }
+
+    // The unit written in SI base units, e.g. N becomes kg*m/s^2
+    pub(crate) fn si_base(dimension: Dimension) -> Unit {
+        let mut unit = Unit::none();
+        for (symbol, exponent) in BASE_SYMBOLS.iter().zip(dimension) {
+            unit.add_factor(symbol, exponent);
+        }
+        unit
+    }
+
+    pub(crate) fn mul(&self, rhs: &Unit) -> Unit {
+        let mut unit = self.clone();
+        for (symbol, exponent) in &rhs.factors {
+            unit.add_factor(symbol, *exponent);
+        }
+        unit
+    }
+
+    pub(crate) fn div(&self, rhs: &Unit) -> Unit {
+        self.mul(&rhs.powi(-1))
+    }
+
+    pub(crate) fn powi(&self, exponent: i32) -> Unit {
+        let factors = self.factors.iter()
+            .map(|(symbol, existing)| (symbol.clone(), existing * exponent))
+            .filter(|(_, exponent)| *exponent != 0)
+            .collect();
+        Unit { factors }
+    }
+}
+
+impl std::fmt::Display for Unit {
+    // Numerator factors joined by "*", then "/" before each denominator factor,
+    // so the text parses back to the same unit
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let write_factor = |symbol: &str, exponent: i32| {
+            if exponent == 1 { symbol.to_string() } else { format!("{}^{}", symbol, exponent) }
+        };
+        let numerator: Vec<String> = self.factors.iter()
+            .filter(|(_, exponent)| *exponent > 0)
+            .map(|(symbol, exponent)| write_factor(symbol, *exponent))
+            .collect();
+        let mut text = if numerator.is_empty() { "1".to_string() } else { numerator.join("*") };
+        for (symbol, exponent) in self.factors.iter().filter(|(_, exponent)| *exponent < 0) {
+            text.push('/');
+            text.push_str(&write_factor(symbol, -exponent));
+        }
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Quantity {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    pub(crate) fn new(value: f64, unit: Unit) -> Quantity {
+        Quantity { value, unit }
+    }
+
+    // Parses "9.81_m/s^2"
+    pub(crate) fn parse(text: &str) -> Option<Result<Quantity, String>> {
+        let (value, unit) = text.split_once('_')?;
+        let value = value.parse::<f64>().ok()?;
+        Some(Unit::parse(unit).map(|unit| Quantity::new(value, unit)))
+    }
+
+    pub(crate) fn is_dimensionless(&self) -> bool {
+        self.unit.dimension() == DIMENSIONLESS
+    }
+
+    // True once the units have cancelled out and only a plain number is left;
+    // angles are dimensionless too but keep their unit
+    pub(crate) fn is_plain_number(&self) -> bool {
+        self.is_dimensionless() && !self.unit.has_angle()
+    }
+
+    // The value once every unit has cancelled out
+    pub(crate) fn si_value(&self) -> f64 {
+        self.value * self.unit.to_si().0
+    }
+
+    pub(crate) fn convert(&self, target: &Unit) -> Result<Quantity, String> {
+        let (from_factor, from_dimension) = self.unit.to_si();
+        let (to_factor, to_dimension) = target.to_si();
+        if from_dimension != to_dimension {
+            return Err(format!("Incompatible units: {} and {}", self.unit, target));
+        }
+        Ok(Quantity::new(self.value * from_factor / to_factor, target.clone()))
+    }
+
+    pub(crate) fn to_si_base(&self) -> Quantity {
+        let (factor, dimension) = self.unit.to_si();
+        Quantity::new(self.value * factor, Unit::si_base(dimension))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stack::item::StackItem;
+
+    fn quantity(text: &str) -> StackItem {
+        StackItem::Quantity(Quantity::parse(text).unwrap().unwrap())
+    }
+
+    #[test]
+    fn equal_units_compare_equal_in_any_order() {
+        assert_eq!(Unit::parse("m/s").unwrap(), Unit::parse("1/s*m").unwrap());
+        assert_eq!(Unit::parse("kg*m^2/s^2").unwrap(), Unit::parse("m^2/s^2*kg").unwrap());
+        let product = (quantity("5_1/s") * quantity("2_m")).unwrap();
+        assert_eq!(product, quantity("10.0_m/s"));
+    }
+
+    #[test]
+    fn units_print_as_text_that_parses_back() {
+        for text in ["m/s^2", "kg*m^2/s^2", "1/s", "A*s/mol", "km/h"] {
+            let unit = Unit::parse(text).unwrap();
+            assert_eq!(Unit::parse(&unit.to_string()).unwrap(), unit, "{}", text);
+        }
+    }
+
+    #[test]
+    fn angles_keep_their_unit() {
+        assert_eq!((quantity("45_deg") * StackItem::Number(2.0)).unwrap(), quantity("90.0_deg"));
+        assert_eq!((quantity("1_rad") + quantity("1_rad")).unwrap(), quantity("2.0_rad"));
+        assert_eq!((quantity("90_deg") / quantity("45_deg")).unwrap(), StackItem::Number(2.0));
+        assert_eq!((quantity("6_m") / quantity("3_m")).unwrap(), StackItem::Number(2.0));
+    }
+}
@@ -0,0 +1,277 @@
+// Fixed-width machine integers for programmer work. Values wrap at the word size
+// and are read as two's complement when the word is signed.
+
+use crate::math::bigint::BigInt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Word {
+    // always masked to `size` bits
+    bits: u64,
+    size: u32,
+    signed: bool,
+}
+
+pub(crate) const WORD_SIZES: [u32; 4] = [8, 16, 32, 64];
+
+// A word size and whether the word is signed
+pub(crate) type WordShape = (u32, bool);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Base {
+    Hex,
+    Dec,
+    Oct,
+    Bin,
+}
+
+impl Word {
+    pub(crate) fn new(bits: u64, size: u32, signed: bool) -> Word {
+        Word { bits: bits & mask(size), size, signed }
+    }
+
+    // Wraps any integer into the word, as a cast would
+    pub(crate) fn from_i128(value: i128, size: u32, signed: bool) -> Word {
+        Word::new(value as u64, size, signed)
+    }
+
+    pub(crate) fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub(crate) fn signed(&self) -> bool {
+        self.signed
+    }
+
+    pub(crate) fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    // The same value wrapped into another size and signedness, as a cast would:
+    // negative signed values are sign-extended, wider values lose their top bits
+    pub(crate) fn resized(&self, size: u32, signed: bool) -> Word {
+        Word::from_i128(self.value(), size, signed)
+    }
+
+    // The number the word represents
+    pub(crate) fn value(&self) -> i128 {
+        if self.signed && (self.bits >> (self.size - 1)) & 1 == 1 {
+            self.bits as i128 - (1i128 << self.size)
+        } else {
+            self.bits as i128
+        }
+    }
+
+    pub(crate) fn to_bigint(self) -> BigInt {
+        // every word value fits in an i64 except the top half of unsigned 64-bit words
+        match i64::try_from(self.value()) {
+            Ok(value) => value.into(),
+            Err(_) => self.bits.into(),
+        }
+    }
+
+    fn with_bits(&self, bits: u64) -> Word {
+        Word::new(bits, self.size, self.signed)
+    }
+
+    pub(crate) fn add(self, rhs: Word) -> Word {
+        self.with_bits(self.bits.wrapping_add(rhs.bits))
+    }
+
+    pub(crate) fn sub(self, rhs: Word) -> Word {
+        self.with_bits(self.bits.wrapping_sub(rhs.bits))
+    }
+
+    pub(crate) fn mul(self, rhs: Word) -> Word {
+        self.with_bits(self.bits.wrapping_mul(rhs.bits))
+    }
+
+    // Truncating division, signed or unsigned according to the word
+    pub(crate) fn div(self, rhs: Word) -> Result<Word, String> {
+        if rhs.bits == 0 {
+            return Err("Division by zero".to_string());
+        }
+        Ok(Word::from_i128(self.value() / rhs.value(), self.size, self.signed))
+    }
+
+    // Floored like the other kinds: a non-zero result has the divisor's sign
+    pub(crate) fn rem(self, rhs: Word) -> Result<Word, String> {
+        if rhs.bits == 0 {
+            return Err("Division by zero".to_string());
+        }
+        let (a, b) = (self.value(), rhs.value());
+        let mut remainder = a % b;
+        if remainder != 0 && (remainder < 0) != (b < 0) {
+            remainder += b;
+        }
+        Ok(Word::from_i128(remainder, self.size, self.signed))
+    }
+
+    pub(crate) fn pow(self, rhs: Word) -> Result<Word, String> {
+        let exponent = rhs.value();
+        if exponent < 0 {
+            return Err("Negative exponents are not supported for binary integers".to_string());
+        }
+        let exponent = u32::try_from(exponent).unwrap_or(u32::MAX);
+        Ok(self.with_bits(self.bits.wrapping_pow(exponent)))
+    }
+
+    pub(crate) fn and(self, rhs: Word) -> Word {
+        self.with_bits(self.bits & rhs.bits)
+    }
+
+    pub(crate) fn or(self, rhs: Word) -> Word {
+        self.with_bits(self.bits | rhs.bits)
+    }
+
+    pub(crate) fn xor(self, rhs: Word) -> Word {
+        self.with_bits(self.bits ^ rhs.bits)
+    }
+
+    pub(crate) fn not(self) -> Word {
+        self.with_bits(!self.bits)
+    }
+
+    pub(crate) fn shl(self, count: u32) -> Word {
+        self.with_bits(self.bits.checked_shl(count).unwrap_or(0))
+    }
+
+    // Logical shift: zeros come in from the left
+    pub(crate) fn shr(self, count: u32) -> Word {
+        self.with_bits(self.bits.checked_shr(count).unwrap_or(0))
+    }
+
+    // Arithmetic shift: copies of the sign bit come in from the left
+    pub(crate) fn asr(self, count: u32) -> Word {
+        let sign_extended = Word::new(self.bits, self.size, true).value() as i64;
+        self.with_bits((sign_extended >> count.min(63)) as u64)
+    }
+
+    pub(crate) fn rotl(self, count: u32) -> Word {
+        let count = count % self.size;
+        if count == 0 {
+            return self;
+        }
+        self.with_bits((self.bits << count) | (self.bits >> (self.size - count)))
+    }
+
+    pub(crate) fn rotr(self, count: u32) -> Word {
+        self.rotl(self.size - count % self.size)
+    }
+
+    pub(crate) fn format(&self, base: Base) -> String {
+        match base {
+            Base::Hex => format!("0x{:X}", self.bits),
+            Base::Oct => format!("0o{:o}", self.bits),
+            Base::Bin => format!("0b{:b}", self.bits),
+            Base::Dec => format!("#{}", self.value()),
+        }
+    }
+
+    // Hex text with the size and signedness attached, which reads back as this exact word
+    pub(crate) fn to_literal(self) -> String {
+        format!("0x{:X}:{}{}", self.bits, self.size, if self.signed { 's' } else { 'u' })
+    }
+}
+
+fn mask(size: u32) -> u64 {
+    if size >= 64 { u64::MAX } else { (1u64 << size) - 1 }
+}
+
+// Parses 0x / 0o / 0b literals and #-prefixed decimal words into raw bits, with an
+// optional ":<size><s|u>" suffix that fixes the word shape (0xFF:8u)
+pub(crate) fn parse_word_literal(text: &str) -> Option<Result<(u64, Option<WordShape>), String>> {
+    let (body, shape) = match text.split_once(':') {
+        Some((body, suffix)) => (body, Some(suffix)),
+        None => (text, None),
+    };
+    let bits = parse_word_bits(body)?;
+    let shape = match shape.map(|suffix| parse_word_shape(suffix).ok_or_else(|| format!("Invalid word size: {}", suffix))) {
+        Some(Ok(shape)) => Some(shape),
+        Some(Err(e)) => return Some(Err(e)),
+        None => None,
+    };
+    Some(bits.map(|bits| (bits, shape)))
+}
+
+fn parse_word_bits(text: &str) -> Option<Result<u64, String>> {
+    let (digits, radix) = if let Some(rest) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        (rest, 16)
+    } else if let Some(rest) = text.strip_prefix("0o").or_else(|| text.strip_prefix("0O")) {
+        (rest, 8)
+    } else if let Some(rest) = text.strip_prefix("0b").or_else(|| text.strip_prefix("0B")) {
+        (rest, 2)
+    } else if let Some(rest) = text.strip_prefix('#') {
+        // decimal words may be negative; they wrap like any other value
+        return Some(rest.parse::<i64>().map(|value| value as u64).or_else(|_| rest.parse::<u64>())
+            .map_err(|_| format!("Invalid binary integer: {}", text)));
+    } else {
+        return None;
+    };
+    let digits = digits.replace('_', "");
+    Some(u64::from_str_radix(&digits, radix).map_err(|_| format!("Invalid binary integer: {}", text)))
+}
+
+fn parse_word_shape(suffix: &str) -> Option<WordShape> {
+    let (size, signed) = match suffix.strip_suffix('s') {
+        Some(size) => (size, true),
+        None => (suffix.strip_suffix('u')?, false),
+    };
+    let size = size.parse::<u32>().ok().filter(|size| WORD_SIZES.contains(size))?;
+    Some((size, signed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_wraps_at_the_word_size() {
+        let byte = |bits| Word::new(bits, 8, false);
+        assert_eq!(byte(0xFF).add(byte(1)), byte(0));
+        assert_eq!(byte(0).sub(byte(1)), byte(0xFF));
+        assert_eq!(byte(16).mul(byte(17)), byte(0x10));
+        assert_eq!(byte(2).pow(byte(9)).unwrap(), byte(0));
+        assert_eq!(Word::new(0x1FF, 8, false), byte(0xFF));
+        assert_eq!(Word::new(u64::MAX, 64, false).add(Word::new(1, 64, false)).bits(), 0);
+    }
+
+    #[test]
+    fn signedness_changes_the_value_not_the_bits() {
+        assert_eq!(Word::new(0xFF, 8, true).value(), -1);
+        assert_eq!(Word::new(0xFF, 8, false).value(), 255);
+        assert_eq!(Word::new(0x80, 8, true).value(), -128);
+        assert_eq!(Word::from_i128(-1, 16, false).bits(), 0xFFFF);
+        // -128 / -1 overflows and wraps back to -128
+        let min = Word::new(0x80, 8, true);
+        assert_eq!(min.div(Word::from_i128(-1, 8, true)).unwrap(), min);
+        assert_eq!(Word::from_i128(-7, 8, true).div(Word::new(2, 8, true)).unwrap().value(), -3);
+        assert_eq!(Word::from_i128(-7, 8, true).rem(Word::new(2, 8, true)).unwrap().value(), 1);
+        assert!(Word::new(1, 8, true).div(Word::new(0, 8, true)).is_err());
+        // resizing sign-extends signed values and truncates wide ones
+        assert_eq!(Word::from_i128(-2, 8, true).resized(16, true).bits(), 0xFFFE);
+        assert_eq!(Word::new(0x1234, 16, false).resized(8, false).bits(), 0x34);
+    }
+
+    #[test]
+    fn rotates_and_shifts() {
+        let byte = |bits| Word::new(bits, 8, false);
+        assert_eq!(byte(0b1000_0001).rotl(1), byte(0b0000_0011));
+        assert_eq!(byte(0b1000_0001).rotr(1), byte(0b1100_0000));
+        assert_eq!(byte(0x5A).rotl(8), byte(0x5A));
+        assert_eq!(byte(0x5A).rotr(0), byte(0x5A));
+        assert_eq!(byte(0x5A).rotl(11), byte(0x5A).rotl(3));
+        assert_eq!(Word::new(1 << 63, 64, false).rotl(1).bits(), 1);
+        assert_eq!(byte(0x81).shl(1), byte(0x02));
+        assert_eq!(byte(0x81).shl(100), byte(0));
+        assert_eq!(byte(0x81).shr(100), byte(0));
+    }
+
+    #[test]
+    fn arithmetic_shift_copies_the_sign_bit() {
+        assert_eq!(Word::new(0x80, 8, false).asr(2).bits(), 0xE0);
+        assert_eq!(Word::new(0x40, 8, false).asr(2).bits(), 0x10);
+        assert_eq!(Word::new(0x80, 8, true).asr(100).bits(), 0xFF);
+        assert_eq!(Word::new(1 << 63, 64, false).asr(63).bits(), u64::MAX);
+        assert_eq!(Word::new(0x8000, 16, true).asr(4).value(), -2048);
+    }
+}
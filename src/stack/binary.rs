@@ -0,0 +1,355 @@
+use crate::data::context::AppContext;
+use crate::math::word::{Base, Word, WORD_SIZES};
+use crate::stack::functions::count_argument;
+use crate::stack::item::{ItemKind, StackItem};
+use crate::stack::logic::{logical, logical_not, LOGICAL};
+use crate::stack::registry::{FunctionSpec, ANY, REAL};
+use crate::stack::transaction::pop_operands;
+
+const WHOLE: &[ItemKind] = &[ItemKind::Word, ItemKind::Integer, ItemKind::Number];
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "and",
+        aliases: &[],
+        arity: 2,
+        accepts: &[LOGICAL, LOGICAL],
+        description: "Bitwise and of binary integers, logical and otherwise",
+        handler: and,
+    },
+    FunctionSpec {
+        name: "or",
+        aliases: &[],
+        arity: 2,
+        accepts: &[LOGICAL, LOGICAL],
+        description: "Bitwise or of binary integers, logical or otherwise",
+        handler: or,
+    },
+    FunctionSpec {
+        name: "xor",
+        aliases: &[],
+        arity: 2,
+        accepts: &[LOGICAL, LOGICAL],
+        description: "Bitwise exclusive or of binary integers, logical otherwise",
+        handler: xor,
+    },
+    FunctionSpec {
+        name: "not",
+        aliases: &[],
+        arity: 1,
+        accepts: &[LOGICAL],
+        description: "Bitwise complement of a binary integer, logical not otherwise",
+        handler: not,
+    },
+    FunctionSpec {
+        name: "shl",
+        aliases: &["sl"],
+        arity: 0,
+        accepts: &[],
+        description: "Shift left by n bits (n from arg or level 1)",
+        handler: shl,
+    },
+    FunctionSpec {
+        name: "shr",
+        aliases: &["sr"],
+        arity: 0,
+        accepts: &[],
+        description: "Logical shift right by n bits (n from arg or level 1)",
+        handler: shr,
+    },
+    FunctionSpec {
+        name: "asr",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Arithmetic shift right by n bits, keeping the sign (n from arg or level 1)",
+        handler: asr,
+    },
+    FunctionSpec {
+        name: "rol",
+        aliases: &["rl"],
+        arity: 0,
+        accepts: &[],
+        description: "Rotate left by n bits within the word (n from arg or level 1)",
+        handler: rol,
+    },
+    FunctionSpec {
+        name: "ror",
+        aliases: &["rr"],
+        arity: 0,
+        accepts: &[],
+        description: "Rotate right by n bits within the word (n from arg or level 1)",
+        handler: ror,
+    },
+    FunctionSpec {
+        name: "word",
+        aliases: &["r->b"],
+        arity: 1,
+        accepts: &[REAL],
+        description: "Convert a whole number to a binary integer of the current word size",
+        handler: word,
+    },
+    FunctionSpec {
+        name: "int",
+        aliases: &["b->r"],
+        arity: 1,
+        accepts: &[&[ItemKind::Word]],
+        description: "Convert a binary integer to an exact integer",
+        handler: int,
+    },
+    FunctionSpec {
+        name: "ws",
+        aliases: &["stws"],
+        arity: 0,
+        accepts: &[],
+        description: "Set the word size to 8, 16, 32 or 64 bits (applies to the stack too)",
+        handler: ws,
+    },
+    FunctionSpec {
+        name: "signed",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Treat binary integers as two's complement signed",
+        handler: signed,
+    },
+    FunctionSpec {
+        name: "unsigned",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Treat binary integers as unsigned",
+        handler: unsigned,
+    },
+    FunctionSpec {
+        name: "hex",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Display binary integers in hexadecimal",
+        handler: hex,
+    },
+    FunctionSpec {
+        name: "dec",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Display binary integers in decimal",
+        handler: dec,
+    },
+    FunctionSpec {
+        name: "oct",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Display binary integers in octal",
+        handler: oct,
+    },
+    FunctionSpec {
+        name: "bin",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Display binary integers in binary",
+        handler: bin,
+    },
+];
+
+// Pops an operand as a word. Words keep their shape; other whole numbers take
+// the shape of `like` or, failing that, the current word settings.
+fn pop_word(context: &mut AppContext, like: Option<&Word>) -> Result<Word, String> {
+    let item = pop_operands(&mut context.stack, 1)?.pop().unwrap();
+    match (&item, like) {
+        (StackItem::Word(word), _) => Ok(*word),
+        (_, Some(like)) => item.to_word(like.size(), like.signed()),
+        (_, None) => item.to_word(context.word.size, context.word.signed),
+    }
+}
+
+fn bitwise<F>(context: &mut AppContext, op: F) -> Result<(), String>
+where
+    F: Fn(Word, Word) -> Word,
+{
+    let operands = pop_operands(&mut context.stack, 2)?;
+    let (size, signed) = match (&operands[0], &operands[1]) {
+        (StackItem::Word(a), StackItem::Word(b)) => (a.size().max(b.size()), a.signed() || b.signed()),
+        (StackItem::Word(word), _) | (_, StackItem::Word(word)) => (word.size(), word.signed()),
+        _ => (context.word.size, context.word.signed),
+    };
+    let a = operands[0].to_word(size, signed)?;
+    let b = operands[1].to_word(size, signed)?;
+    context.stack.push(StackItem::Word(op(a, b)));
+    Ok(())
+}
+
+// and/or/xor/not are bitwise as soon as a binary integer is involved
+fn has_word(context: &AppContext, depth: usize) -> bool {
+    context.stack.iter().rev().take(depth).any(|item| matches!(item, StackItem::Word(_)))
+}
+
+fn and(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    match has_word(context, 2) {
+        true => bitwise(context, Word::and),
+        false => logical(context, |a, b| a && b),
+    }
+}
+
+fn or(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    match has_word(context, 2) {
+        true => bitwise(context, Word::or),
+        false => logical(context, |a, b| a || b),
+    }
+}
+
+fn xor(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    match has_word(context, 2) {
+        true => bitwise(context, Word::xor),
+        false => logical(context, |a, b| a != b),
+    }
+}
+
+fn not(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    if !has_word(context, 1) {
+        return logical_not(context);
+    }
+    let word = pop_word(context, None)?;
+    context.stack.push(StackItem::Word(word.not()));
+    Ok(())
+}
+
+fn shift<F>(args: Vec<String>, context: &mut AppContext, op: F) -> Result<(), String>
+where
+    F: Fn(Word, u32) -> Word,
+{
+    let count = count_argument(&args, context)?;
+    let word = pop_word(context, None)?;
+    context.stack.push(StackItem::Word(op(word, count.min(u32::MAX as usize) as u32)));
+    Ok(())
+}
+
+fn shl(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    shift(args, context, Word::shl)
+}
+
+fn shr(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    shift(args, context, Word::shr)
+}
+
+fn asr(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    shift(args, context, Word::asr)
+}
+
+fn rol(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    shift(args, context, Word::rotl)
+}
+
+fn ror(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    shift(args, context, Word::rotr)
+}
+
+fn word(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let word = pop_word(context, None)?;
+    context.stack.push(StackItem::Word(word));
+    Ok(())
+}
+
+fn int(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let word = pop_word(context, None)?;
+    context.stack.push(StackItem::Integer(word.to_bigint()));
+    Ok(())
+}
+
+// Word settings act globally, like on a programmer's calculator: changing them
+// also reshapes the binary integers already on the stack
+fn apply_word_settings(context: &mut AppContext, size: u32, signed: bool) {
+    context.word.size = size;
+    context.word.signed = signed;
+    for item in context.stack.iter_mut() {
+        if let StackItem::Word(word) = item {
+            *word = word.resized(size, signed);
+        }
+    }
+}
+
+fn ws(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let count = count_argument(&args, context)?;
+    let size = u32::try_from(count).ok()
+        .filter(|size| WORD_SIZES.contains(size))
+        .ok_or_else(|| format!("Word size must be one of 8, 16, 32 or 64, got {}", count))?;
+    apply_word_settings(context, size, context.word.signed);
+    Ok(())
+}
+
+fn signed(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply_word_settings(context, context.word.size, true);
+    Ok(())
+}
+
+fn unsigned(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply_word_settings(context, context.word.size, false);
+    Ok(())
+}
+
+fn hex(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.display.base = Base::Hex;
+    Ok(())
+}
+
+fn dec(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.display.base = Base::Dec;
+    Ok(())
+}
+
+fn oct(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.display.base = Base::Oct;
+    Ok(())
+}
+
+fn bin(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.display.base = Base::Bin;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stack::functions::route_function_call;
+
+    fn call(context: &mut AppContext, name: &str, args: &[&str]) -> Result<(), String> {
+        route_function_call(name.to_string(), args.iter().map(|arg| arg.to_string()).collect(), context)
+    }
+
+    #[test]
+    fn ws_rejects_sizes_that_only_truncate_to_valid_ones() {
+        let mut context = AppContext::default();
+        for size in ["4294967304", "18446744073709551624", "12", "0"] {
+            assert!(call(&mut context, "ws", &[size]).is_err(), "{} was accepted", size);
+        }
+        assert_eq!(context.word.size, 64);
+        call(&mut context, "ws", &["16"]).unwrap();
+        assert_eq!(context.word.size, 16);
+    }
+
+    #[test]
+    fn ws_reshapes_words_on_the_stack() {
+        let mut context = AppContext::default();
+        context.stack.push(StackItem::Word(Word::new(0x1234, 64, false)));
+        call(&mut context, "ws", &["8"]).unwrap();
+        call(&mut context, "signed", &[]).unwrap();
+        assert_eq!(context.stack, vec![StackItem::Word(Word::new(0x34, 8, true))]);
+    }
+
+    #[test]
+    fn shifts_and_rotates_take_their_count() {
+        let mut context = AppContext::default();
+        context.stack.push(StackItem::Word(Word::new(0x81, 8, false)));
+        call(&mut context, "rol", &["1"]).unwrap();
+        assert_eq!(context.stack, vec![StackItem::Word(Word::new(0x03, 8, false))]);
+        call(&mut context, "ror", &["2"]).unwrap();
+        assert_eq!(context.stack, vec![StackItem::Word(Word::new(0xC0, 8, false))]);
+        call(&mut context, "asr", &["3"]).unwrap();
+        assert_eq!(context.stack, vec![StackItem::Word(Word::new(0xF8, 8, false))]);
+        call(&mut context, "shr", &["3"]).unwrap();
+        assert_eq!(context.stack, vec![StackItem::Word(Word::new(0x1F, 8, false))]);
+    }
+}
@@ -0,0 +1,201 @@
+use crate::data::context::AppContext;
+use crate::math::complex::Complex;
+use crate::stack::item::StackItem;
+use crate::stack::registry::{FunctionSpec, NUMERIC, REAL, SCALAR};
+use crate::stack::transaction::pop_operands;
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "re",
+        aliases: &["real"],
+        arity: 1,
+        accepts: &[SCALAR],
+        description: "Real part",
+        handler: re,
+    },
+    FunctionSpec {
+        name: "im",
+        aliases: &["imag"],
+        arity: 1,
+        accepts: &[SCALAR],
+        description: "Imaginary part",
+        handler: im,
+    },
+    FunctionSpec {
+        name: "conj",
+        aliases: &[],
+        arity: 1,
+        accepts: &[SCALAR],
+        description: "Complex conjugate",
+        handler: conj,
+    },
+    FunctionSpec {
+        name: "abs",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Absolute value or complex modulus (element-wise on arrays)",
+        handler: abs,
+    },
+    FunctionSpec {
+        name: "arg",
+        aliases: &["angle"],
+        arity: 1,
+        accepts: &[SCALAR],
+        description: "Angle of a complex number from the positive real axis, in the angle mode",
+        handler: arg,
+    },
+    FunctionSpec {
+        name: "polar",
+        aliases: &[],
+        arity: 1,
+        accepts: &[SCALAR],
+        description: "Split a complex number into modulus and angle",
+        handler: polar,
+    },
+    FunctionSpec {
+        name: "rect",
+        aliases: &[],
+        arity: 2,
+        accepts: &[REAL, REAL],
+        description: "Build a complex number from modulus (level 2) and angle (level 1) in the angle mode",
+        handler: rect,
+    },
+    FunctionSpec {
+        name: "cplx",
+        aliases: &["complex"],
+        arity: 2,
+        accepts: &[REAL, REAL],
+        description: "Build a complex number from real (level 2) and imaginary (level 1) parts",
+        handler: cplx,
+    },
+    FunctionSpec {
+        name: "sqrt",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Square root; negative numbers give complex results",
+        handler: sqrt,
+    },
+    FunctionSpec {
+        name: "ln",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Natural logarithm; negative numbers give complex results",
+        handler: ln,
+    },
+];
+
+fn pop_one(context: &mut AppContext) -> Result<StackItem, String> {
+    Ok(pop_operands(&mut context.stack, 1)?.pop().unwrap())
+}
+
+fn re(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let c = pop_one(context)?.as_complex()?;
+    context.stack.push(StackItem::Number(c.re));
+    Ok(())
+}
+
+fn im(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let c = pop_one(context)?.as_complex()?;
+    context.stack.push(StackItem::Number(c.im));
+    Ok(())
+}
+
+fn conj(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let item = pop_one(context)?;
+    let result = match item {
+        StackItem::Complex(c) => StackItem::Complex(c.conj()),
+        other => other,
+    };
+    context.stack.push(result);
+    Ok(())
+}
+
+fn abs(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let result = match pop_one(context)? {
+        StackItem::Complex(c) => StackItem::Number(c.abs()),
+        StackItem::Integer(int) => StackItem::Integer(int.abs()),
+        StackItem::Rational(rational) => StackItem::Rational(rational.abs()),
+        other => other.map_math("abs", |x| Some(x.abs()), |c| Complex::from(c.abs()))?,
+    };
+    context.stack.push(result);
+    Ok(())
+}
+
+fn arg(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let c = pop_one(context)?.as_complex()?;
+    context.stack.push(StackItem::Number(context.angle.radians_to_mode(c.arg())));
+    Ok(())
+}
+
+fn polar(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let c = pop_one(context)?.as_complex()?;
+    let theta = context.angle.radians_to_mode(c.arg());
+    context.stack.extend([StackItem::Number(c.abs()), StackItem::Number(theta)]);
+    Ok(())
+}
+
+fn rect(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let operands = pop_operands(&mut context.stack, 2)?;
+    let (r, theta) = (operands[0].as_number()?, operands[1].as_number()?);
+    let (sin, cos) = context.angle.sin_cos(theta);
+    context.stack.push(StackItem::Complex(Complex::new(r * cos, r * sin)));
+    Ok(())
+}
+
+fn cplx(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let operands = pop_operands(&mut context.stack, 2)?;
+    let (re, im) = (operands[0].as_number()?, operands[1].as_number()?);
+    context.stack.push(StackItem::Complex(Complex::new(re, im)));
+    Ok(())
+}
+
+fn sqrt(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let item = pop_one(context)?;
+    let result = item.map_math("sqrt", |x| (x >= 0.0).then(|| x.sqrt()), Complex::sqrt)?;
+    context.stack.push(result);
+    Ok(())
+}
+
+fn ln(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let item = pop_one(context)?;
+    let result = item.map_math("ln", |x| (x >= 0.0).then(|| x.ln()), Complex::ln)?;
+    context.stack.push(result);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::settings::AngleMode;
+    use crate::stack::functions::route_function_call;
+
+    fn call(context: &mut AppContext, name: &str) -> Result<(), String> {
+        route_function_call(name.to_string(), Vec::new(), context)
+    }
+
+    #[test]
+    fn polar_and_rect_use_the_angle_mode() {
+        let mut context = AppContext { angle: AngleMode::Deg, ..Default::default() };
+        context.stack.push(StackItem::Complex(Complex::new(0.0, 2.0)));
+        call(&mut context, "polar").unwrap();
+        assert_eq!(context.stack, vec![StackItem::Number(2.0), StackItem::Number(90.0)]);
+        call(&mut context, "rect").unwrap();
+        match context.stack.as_slice() {
+            [StackItem::Complex(c)] => assert!((*c - Complex::new(0.0, 2.0)).abs() < 1e-12),
+            other => panic!("unexpected stack {:?}", other),
+        }
+    }
+
+    #[test]
+    fn zero_to_an_imaginary_power_is_an_error() {
+        let zero = StackItem::Complex(Complex::from(0.0));
+        assert!(zero.clone().pow(StackItem::Complex(Complex::new(0.0, 1.0))).is_err());
+        match zero.pow(StackItem::Complex(Complex::new(-1.0, 1.0))) {
+            Ok(StackItem::Complex(c)) => assert_eq!(c.re, f64::INFINITY),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::data::context::AppContext;
+use crate::data::list_view::ListView;
+use crate::math::constants::{self, Constant, CONSTANTS};
+use crate::math::units::{Quantity, Unit};
+use crate::stack::item::StackItem;
+use crate::stack::registry::{FunctionSpec, ANY};
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "const",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Push the named constant (.const c); unknown function names are tried as constants too",
+        handler: push_named,
+    },
+    FunctionSpec {
+        name: "consts",
+        aliases: &["constants"],
+        arity: 0,
+        accepts: &[],
+        description: "List the constants catalog",
+        handler: consts,
+    },
+];
+
+// Physical constants become quantities, pure numbers stay numbers
+pub(crate) fn constant_item(constant: &Constant) -> StackItem {
+    if constant.unit.is_empty() {
+        return StackItem::Number(constant.value);
+    }
+    let unit = Unit::parse(constant.unit).expect("catalog units are valid");
+    StackItem::Quantity(Quantity::new(constant.value, unit))
+}
+
+// e.g. "c = 299792458_m/s - Speed of light in vacuum"
+pub(crate) fn describe(constant: &Constant) -> String {
+    let mut name = constant.name.to_string();
+    if !constant.aliases.is_empty() {
+        name.push_str(&format!(" ({})", constant.aliases.join(", ")));
+    }
+    let value = match constant.unit {
+        "" => constant.value.to_string(),
+        unit => format!("{}_{}", constant.value, unit),
+    };
+    format!("{:<16} {:<28} {}", name, value, constant.description)
+}
+
+fn push_named(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let name = args.first().ok_or("Missing constant name")?;
+    let constant = constants::lookup(name).ok_or_else(|| format!("Unknown constant: {}", name))?;
+    context.stack.push(constant_item(constant));
+    Ok(())
+}
+
+fn consts(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let lines = CONSTANTS.iter().map(describe).collect();
+    context.list_view = Some(ListView::new("constants", lines));
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::data::context::AppContext;
+use crate::math::datetime::{self, Date, Duration};
+use crate::math::bigint::BigInt;
+use crate::stack::item::{ItemKind, StackItem};
+use crate::stack::registry::{FunctionSpec, ANY, REAL};
+use crate::stack::transaction::pop_operands;
+
+const DATE: &[ItemKind] = &[ItemKind::Date];
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "dow",
+        aliases: &["weekday"],
+        arity: 1,
+        accepts: &[DATE],
+        description: "Day of the week of a date",
+        handler: dow,
+    },
+    FunctionSpec {
+        name: "days",
+        aliases: &["ddays"],
+        arity: 2,
+        accepts: &[DATE, DATE],
+        description: "Whole days from the level 2 date to the level 1 date",
+        handler: days,
+    },
+    FunctionSpec {
+        name: "hms->",
+        aliases: &["fromhms"],
+        arity: 1,
+        accepts: &[REAL],
+        description: "Convert H.MMSS to decimal hours",
+        handler: from_hms,
+    },
+    FunctionSpec {
+        name: "->hms",
+        aliases: &["tohms"],
+        arity: 1,
+        accepts: &[REAL],
+        description: "Convert decimal hours to H.MMSS",
+        handler: to_hms,
+    },
+    FunctionSpec {
+        name: "hours",
+        aliases: &[],
+        arity: 1,
+        accepts: &[&[ItemKind::Duration, ItemKind::Number, ItemKind::Integer, ItemKind::Rational]],
+        description: "Duration as decimal hours, or decimal hours as a duration",
+        handler: hours,
+    },
+    FunctionSpec {
+        name: "today",
+        aliases: &["date"],
+        arity: 0,
+        accepts: &[],
+        description: "Push today's date (UTC)",
+        handler: today,
+    },
+    FunctionSpec {
+        name: "now",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Push the current date and time (UTC)",
+        handler: now,
+    },
+];
+
+fn pop_date(context: &mut AppContext) -> Result<Date, String> {
+    match pop_operands(&mut context.stack, 1)?.pop().unwrap() {
+        StackItem::Date(date) => Ok(date),
+        other => Err(format!("Expected a date, got {}", other.kind().name())),
+    }
+}
+
+fn dow(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let date = pop_date(context)?;
+    context.stack.push(StackItem::String(date.weekday_name().to_string()));
+    Ok(())
+}
+
+fn days(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let end = pop_date(context)?;
+    let start = pop_date(context)?;
+    context.stack.push(StackItem::Integer(BigInt::from(end.days() - start.days())));
+    Ok(())
+}
+
+fn from_hms(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let value = pop_operands(&mut context.stack, 1)?.pop().unwrap().as_number()?;
+    context.stack.push(StackItem::Number(datetime::from_hms(value)));
+    Ok(())
+}
+
+fn to_hms(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let value = pop_operands(&mut context.stack, 1)?.pop().unwrap().as_number()?;
+    context.stack.push(StackItem::Number(datetime::to_hms(value)));
+    Ok(())
+}
+
+fn hours(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let result = match pop_operands(&mut context.stack, 1)?.pop().unwrap() {
+        StackItem::Duration(duration) => StackItem::Number(duration.hours()),
+        other => {
+            let seconds = (other.as_number()? * 3600.0).round();
+            if seconds.abs() >= i64::MAX as f64 || seconds.is_nan() {
+                return Err("Duration out of range".to_string());
+            }
+            StackItem::Duration(Duration::from_seconds(seconds as i64))
+        },
+    };
+    context.stack.push(result);
+    Ok(())
+}
+
+fn current_seconds() -> Result<i64, String> {
+    let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|_| "System clock is before 1970".to_string())?;
+    Ok(elapsed.as_secs() as i64)
+}
+
+fn today(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let seconds = current_seconds()?;
+    context.stack.push(StackItem::Date(Date::from_seconds(seconds - seconds % 86400, true)?));
+    Ok(())
+}
+
+fn now(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.stack.push(StackItem::Date(Date::from_seconds(current_seconds()?, false)?));
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use crate::data::context::AppContext;
+use crate::data::settings::NumberFormat;
+use crate::stack::functions::count_argument;
+use crate::stack::registry::{FunctionSpec, ANY};
+
+// More places than a float carries only adds noise
+const MAX_DIGITS: usize = 17;
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "std",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Show numbers in full, switching to scientific for very large or small ones",
+        handler: std,
+    },
+    FunctionSpec {
+        name: "fix",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Show numbers with n decimal places (n from arg or level 1)",
+        handler: fix,
+    },
+    FunctionSpec {
+        name: "sci",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Show numbers in scientific notation with n decimal places (n from arg or level 1)",
+        handler: sci,
+    },
+    FunctionSpec {
+        name: "eng",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Show numbers in engineering notation with n + 1 significant digits (n from arg or level 1)",
+        handler: eng,
+    },
+];
+
+fn digits_argument(args: &[String], context: &mut AppContext) -> Result<usize, String> {
+    let digits = count_argument(args, context)?;
+    if digits > MAX_DIGITS {
+        return Err(format!("At most {} digits, got {}", MAX_DIGITS, digits));
+    }
+    Ok(digits)
+}
+
+fn std(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.display.format = NumberFormat::Std;
+    Ok(())
+}
+
+fn fix(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.display.format = NumberFormat::Fix(digits_argument(&args, context)?);
+    Ok(())
+}
+
+fn sci(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.display.format = NumberFormat::Sci(digits_argument(&args, context)?);
+    Ok(())
+}
+
+fn eng(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.display.format = NumberFormat::Eng(digits_argument(&args, context)?);
+    Ok(())
+}
@@ -1,27 +1,189 @@
 use crate::data::context::AppContext;
+use crate::data::list_view::ListView;
+use crate::math::bigint::BigInt;
+use crate::math::constants as math_constants;
+use crate::math::rational::Rational;
+use crate::stack::constants::{constant_item, describe};
 use crate::stack::item::StackItem;
+use crate::stack::registry::{self, FunctionSpec, ANY, NUMERIC};
+use crate::stack::transaction::{pop_operands, require_depth, run_transaction};
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "add",
+        aliases: &["+"],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "Add level 2 and level 1",
+        handler: add,
+    },
+    FunctionSpec {
+        name: "dup",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ANY],
+        description: "Duplicate level 1",
+        handler: dup,
+    },
+    FunctionSpec {
+        name: "drop",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ANY],
+        description: "Remove level 1",
+        handler: drop_top,
+    },
+    FunctionSpec {
+        name: "swap",
+        aliases: &[],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "Exchange levels 1 and 2",
+        handler: swap,
+    },
+    FunctionSpec {
+        name: "over",
+        aliases: &[],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "Copy level 2 to the top",
+        handler: over,
+    },
+    FunctionSpec {
+        name: "rot",
+        aliases: &[],
+        arity: 3,
+        accepts: &[ANY, ANY, ANY],
+        description: "Move level 3 to the top",
+        handler: rot,
+    },
+    FunctionSpec {
+        name: "-rot",
+        aliases: &["unrot"],
+        arity: 3,
+        accepts: &[ANY, ANY, ANY],
+        description: "Move level 1 down to level 3",
+        handler: unrot,
+    },
+    FunctionSpec {
+        name: "nip",
+        aliases: &[],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "Remove level 2",
+        handler: nip,
+    },
+    FunctionSpec {
+        name: "tuck",
+        aliases: &[],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "Copy level 1 below level 2",
+        handler: tuck,
+    },
+    FunctionSpec {
+        name: "pick",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Copy level n to the top (n from arg or level 1)",
+        handler: pick,
+    },
+    FunctionSpec {
+        name: "roll",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Move level n to the top (n from arg or level 1)",
+        handler: roll,
+    },
+    FunctionSpec {
+        name: "rolld",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Move level 1 down to level n (n from arg or level 1)",
+        handler: rolld,
+    },
+    FunctionSpec {
+        name: "dupn",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Duplicate the top n levels (n from arg or level 1)",
+        handler: dupn,
+    },
+    FunctionSpec {
+        name: "dropn",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Remove the top n levels (n from arg or level 1)",
+        handler: dropn,
+    },
+    FunctionSpec {
+        name: "keep",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Keep only the top n levels (n from arg or level 1)",
+        handler: keep,
+    },
+    FunctionSpec {
+        name: "depth",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Push the number of items on the stack",
+        handler: depth,
+    },
+    FunctionSpec {
+        name: "approx",
+        aliases: &["->num"],
+        arity: 1,
+        accepts: &[ANY],
+        description: "Convert an exact integer or fraction to a float",
+        handler: approx,
+    },
+    FunctionSpec {
+        name: "exact",
+        aliases: &["->q"],
+        arity: 1,
+        accepts: &[ANY],
+        description: "Convert a float to the exact fraction of its decimal value",
+        handler: exact,
+    },
+    FunctionSpec {
+        name: "help",
+        aliases: &["?"],
+        arity: 0,
+        accepts: &[],
+        description: "Describe a function (.help name) or list them all",
+        handler: help,
+    },
+];
 
 pub(crate) fn route_function_call(name: String, args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
-    match name.as_str() {
-        "add" => add(args, context),
-        "dup" => dup(args, context),
-        _ => Err(format!("Unknown function: {}", name)),
-    }
+    let Some(spec) = registry::lookup(&name) else {
+        // a bare constant name pushes the constant
+        let constant = math_constants::lookup(&name).ok_or_else(|| format!("Unknown function: {}", name))?;
+        return run_transaction(context, |context| {
+            context.stack.push(constant_item(constant));
+            Ok(())
+        });
+    };
+    spec.validate(&context.stack)?;
+    // every call runs as a transaction so a failure leaves the stack untouched
+    run_transaction(context, |context| (spec.handler)(args, context))
+        .map_err(|e| format!("{}: {}", spec.name, e))
 }
 
-fn add(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
-    // Basic implementation of add function
-    // This example simply adds two numbers from the stack
-    let a = context.stack.pop().ok_or("Error: Stack is empty")?;
-    let b = context.stack.pop().ok_or("Error: Stack is empty")?;
-
-    match (a, b) {
-        (StackItem::Number(num1), StackItem::Number(num2)) => {
-            context.stack.push(StackItem::Number(num1 + num2));
-            Ok(())
-        },
-        _ => Err("Error: Both arguments must be numbers".to_string()),
-    }
+fn add(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let mut operands = pop_operands(&mut context.stack, 2)?;
+    let rhs = operands.pop().unwrap();
+    let lhs = operands.pop().unwrap();
+    context.stack.push((lhs + rhs)?);
+    Ok(())
 }
 
 fn dup(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
@@ -31,7 +193,231 @@ fn dup(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
         context.stack.push(top_element);
         Ok(())
     } else {
-        Err("Error: Stack is empty".to_string())
+        Err("Stack is empty".to_string())
+    }
+}
+
+
+// Larger counts are refused outright; no stack, matrix or shift needs one
+const MAX_COUNT: usize = 1_000_000;
+
+// Reads the count for words like pick and roll, either from the first function
+// argument (".pick 3") or, failing that, by popping it off the stack ("3 .pick")
+pub(crate) fn count_argument(args: &[String], context: &mut AppContext) -> Result<usize, String> {
+    let count = match args.first() {
+        Some(arg) => arg.parse::<usize>().map_err(|_| format!("Expected a non-negative integer, got {}", arg))?,
+        // oversized values saturate and are caught by the limit below
+        None => match context.stack.pop() {
+            Some(StackItem::Number(num)) if num >= 0.0 && num.fract() == 0.0 => num as usize,
+            Some(StackItem::Integer(int)) if !int.is_negative() => int.to_i64().map_or(usize::MAX, |n| n as usize),
+            Some(_) => return Err("Level 1 must be a non-negative integer count".to_string()),
+            None => return Err("Missing count argument".to_string()),
+        },
+    };
+    if count > MAX_COUNT {
+        return Err(format!("Count is too large; the limit is {}", MAX_COUNT));
+    }
+    Ok(count)
+}
+
+fn drop_top(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.stack.pop();
+    Ok(())
+}
+
+fn swap(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let len = context.stack.len();
+    context.stack.swap(len - 1, len - 2);
+    Ok(())
+}
+
+fn over(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let second = context.stack[context.stack.len() - 2].clone();
+    context.stack.push(second);
+    Ok(())
+}
+
+fn rot(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let third = context.stack.remove(context.stack.len() - 3);
+    context.stack.push(third);
+    Ok(())
+}
+
+fn unrot(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let top = context.stack.pop().unwrap();
+    context.stack.insert(context.stack.len() - 2, top);
+    Ok(())
+}
+
+fn nip(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.stack.remove(context.stack.len() - 2);
+    Ok(())
+}
+
+fn tuck(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let top = context.stack[context.stack.len() - 1].clone();
+    context.stack.insert(context.stack.len() - 2, top);
+    Ok(())
+}
+
+fn pick(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let n = count_argument(&args, context)?;
+    if n == 0 {
+        return Err("Level must be at least 1".to_string());
+    }
+    require_depth(&context.stack, n)?;
+    let item = context.stack[context.stack.len() - n].clone();
+    context.stack.push(item);
+    Ok(())
+}
+
+fn roll(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let n = count_argument(&args, context)?;
+    if n == 0 {
+        return Ok(());
+    }
+    require_depth(&context.stack, n)?;
+    let item = context.stack.remove(context.stack.len() - n);
+    context.stack.push(item);
+    Ok(())
+}
+
+fn rolld(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let n = count_argument(&args, context)?;
+    if n == 0 {
+        return Ok(());
+    }
+    require_depth(&context.stack, n)?;
+    let top = context.stack.pop().unwrap();
+    context.stack.insert(context.stack.len() + 1 - n, top);
+    Ok(())
+}
+
+fn dupn(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let n = count_argument(&args, context)?;
+    require_depth(&context.stack, n)?;
+    let top_n = context.stack[context.stack.len() - n..].to_vec();
+    context.stack.extend(top_n);
+    Ok(())
+}
+
+fn dropn(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let n = count_argument(&args, context)?;
+    pop_operands(&mut context.stack, n)?;
+    Ok(())
+}
+
+fn keep(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let n = count_argument(&args, context)?;
+    require_depth(&context.stack, n)?;
+    let len = context.stack.len();
+    context.stack.drain(..len - n);
+    Ok(())
+}
+
+fn depth(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.stack.push(StackItem::Integer(BigInt::from(context.stack.len() as u64)));
+    Ok(())
+}
+
+fn approx(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let item = context.stack.pop().unwrap();
+    let result = match item.as_exact() {
+        Some(_) => StackItem::Number(item.as_number()?),
+        None => item,
+    };
+    context.stack.push(result);
+    Ok(())
+}
+
+fn exact(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let result = match context.stack.pop().unwrap() {
+        StackItem::Number(num) => StackItem::from_exact(Rational::from_f64(num)?),
+        other => other,
+    };
+    context.stack.push(result);
+    Ok(())
+}
+
+fn help(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    if let Some(name) = args.first() {
+        match (registry::lookup(name), math_constants::lookup(name)) {
+            (Some(spec), _) => context.report_info(format!("{} - {}", spec.signature(), spec.description)),
+            (None, Some(constant)) => context.report_info(describe(constant)),
+            (None, None) => return Err(format!("Unknown function: {}", name)),
+        }
+    } else {
+        let lines = registry::builtins()
+            .map(|spec| format!("{:<24} {}", spec.signature(), spec.description))
+            .collect();
+        context.list_view = Some(ListView::new("functions", lines));
     }
+    Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(values: &[i64]) -> Vec<StackItem> {
+        values.iter().map(|&value| StackItem::Integer(BigInt::from(value))).collect()
+    }
+
+    // Runs `name` with the count as an argument on the stack 1 2 3 4 and returns the result
+    fn run(name: &str, n: usize) -> Result<Vec<StackItem>, String> {
+        let mut context = AppContext { stack: numbers(&[1, 2, 3, 4]), ..Default::default() };
+        route_function_call(name.to_string(), vec![n.to_string()], &mut context)?;
+        Ok(context.stack)
+    }
+
+    #[test]
+    fn counted_words_with_zero() {
+        assert!(run("pick", 0).is_err());
+        assert_eq!(run("roll", 0), Ok(numbers(&[1, 2, 3, 4])));
+        assert_eq!(run("rolld", 0), Ok(numbers(&[1, 2, 3, 4])));
+        assert_eq!(run("dupn", 0), Ok(numbers(&[1, 2, 3, 4])));
+        assert_eq!(run("dropn", 0), Ok(numbers(&[1, 2, 3, 4])));
+        assert_eq!(run("keep", 0), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn counted_words_with_the_whole_stack() {
+        assert_eq!(run("pick", 4), Ok(numbers(&[1, 2, 3, 4, 1])));
+        assert_eq!(run("roll", 4), Ok(numbers(&[2, 3, 4, 1])));
+        assert_eq!(run("rolld", 4), Ok(numbers(&[4, 1, 2, 3])));
+        assert_eq!(run("dupn", 4), Ok(numbers(&[1, 2, 3, 4, 1, 2, 3, 4])));
+        assert_eq!(run("dropn", 4), Ok(Vec::new()));
+        assert_eq!(run("keep", 4), Ok(numbers(&[1, 2, 3, 4])));
+    }
+
+    #[test]
+    fn counted_words_beyond_the_stack_fail() {
+        for name in ["pick", "roll", "rolld", "dupn", "dropn", "keep"] {
+            assert!(run(name, 5).is_err(), "{} 5 succeeded", name);
+            assert!(run(name, MAX_COUNT + 1).is_err(), "{} with a huge count succeeded", name);
+        }
+    }
+
+    #[test]
+    fn counts_come_from_the_stack_after_it_is_popped() {
+        let mut context = AppContext { stack: numbers(&[7, 8, 9, 2]), ..Default::default() };
+        route_function_call("pick".to_string(), Vec::new(), &mut context).unwrap();
+        assert_eq!(context.stack, numbers(&[7, 8, 9, 8]));
+
+        context.stack.push(StackItem::Number(1e300));
+        assert!(route_function_call("dupn".to_string(), Vec::new(), &mut context).is_err());
+        context.stack.push(StackItem::Number(-1.0));
+        assert!(route_function_call("dupn".to_string(), Vec::new(), &mut context).is_err());
+        // both failures left the stack as it was, count included
+        assert_eq!(context.stack.pop(), Some(StackItem::Number(-1.0)));
+        assert_eq!(context.stack.pop(), Some(StackItem::Number(1e300)));
+        assert_eq!(context.stack, numbers(&[7, 8, 9, 8]));
+    }
+
+    #[test]
+    fn depth_pushes_an_integer() {
+        let mut context = AppContext { stack: numbers(&[5, 6]), ..Default::default() };
+        route_function_call("depth".to_string(), Vec::new(), &mut context).unwrap();
+        assert_eq!(context.stack, numbers(&[5, 6, 2]));
+    }
+}
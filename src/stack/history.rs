@@ -0,0 +1,127 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::data::settings::{AngleMode, DisplaySettings, WordSettings};
+use crate::stack::item::StackItem;
+
+// How many stack states undo can step back through
+pub(crate) const HISTORY_LIMIT: usize = 100;
+
+// What a command can change and undo puts back: the stack, the variables and the
+// modes that commands like fix, hex, ws or deg set
+#[derive(Clone, PartialEq)]
+pub(crate) struct Snapshot {
+    pub stack: Vec<StackItem>,
+    pub variables: BTreeMap<String, StackItem>,
+    pub display: DisplaySettings,
+    pub word: WordSettings,
+    pub angle: AngleMode,
+}
+
+// Earlier states for undo and redo, oldest first
+pub(crate) struct History<T> {
+    undo: VecDeque<T>,
+    redo: Vec<T>,
+    limit: usize,
+}
+
+// Snapshots taken before each command
+pub(crate) type StackHistory = History<Snapshot>;
+
+impl<T> History<T> {
+    pub(crate) fn new(limit: usize) -> History<T> {
+        History { undo: VecDeque::new(), redo: Vec::new(), limit }
+    }
+
+    // Remembers the state as it was before a command changed it. A new command
+    // invalidates anything that could have been redone.
+    pub(crate) fn record(&mut self, snapshot: T) {
+        if self.undo.len() == self.limit {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(snapshot);
+        self.redo.clear();
+    }
+
+    // Swaps the current state for the previous one. Returns false if there is nothing to undo.
+    pub(crate) fn undo(&mut self, state: &mut T) -> bool {
+        match self.undo.pop_back() {
+            Some(previous) => {
+                self.redo.push(std::mem::replace(state, previous));
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Reapplies the most recently undone state. Returns false if there is nothing to redo.
+    pub(crate) fn redo(&mut self, state: &mut T) -> bool {
+        match self.redo.pop() {
+            Some(next) => {
+                self.undo.push_back(std::mem::replace(state, next));
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_then_redo_walks_back_and_forth() {
+        let mut history = History::new(HISTORY_LIMIT);
+        let mut state = 0;
+        for next in 1..=3 {
+            history.record(state);
+            state = next;
+        }
+        assert!(history.undo(&mut state));
+        assert!(history.undo(&mut state));
+        assert_eq!(state, 1);
+        assert!(history.redo(&mut state));
+        assert_eq!(state, 2);
+        assert!(history.redo(&mut state));
+        assert_eq!(state, 3);
+        assert!(!history.redo(&mut state));
+        assert_eq!(state, 3);
+    }
+
+    #[test]
+    fn only_the_last_hundred_states_are_kept() {
+        let mut history = History::new(HISTORY_LIMIT);
+        let mut state = 0;
+        for next in 1..=HISTORY_LIMIT + 50 {
+            history.record(state);
+            state = next;
+        }
+        let mut undone = 0;
+        while history.undo(&mut state) {
+            undone += 1;
+        }
+        assert_eq!(undone, HISTORY_LIMIT);
+        assert_eq!(state, 50);
+        // everything undone can be redone
+        let mut redone = 0;
+        while history.redo(&mut state) {
+            redone += 1;
+        }
+        assert_eq!(redone, HISTORY_LIMIT);
+        assert_eq!(state, HISTORY_LIMIT + 50);
+    }
+
+    #[test]
+    fn a_new_action_clears_redo() {
+        let mut history = History::new(HISTORY_LIMIT);
+        let mut state = 0;
+        history.record(state);
+        state = 1;
+        assert!(history.undo(&mut state));
+        history.record(state);
+        state = 2;
+        assert!(!history.redo(&mut state));
+        assert!(history.undo(&mut state));
+        assert_eq!(state, 0);
+    }
+}
@@ -1,14 +1,244 @@
+use crate::math::bigint::BigInt;
+use crate::math::complex::Complex;
+use crate::math::rational::Rational;
+use crate::math::word::{Word, WORD_SIZES};
+use crate::math::units::{Quantity, Unit};
+use crate::math::datetime::{Date, Duration};
+use crate::math::linalg::{self, shape};
+use crate::control::parsing::format_string_literal;
+use crate::utils::json::Json;
+
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) enum StackItem {
     Number(f64),
+    // rows of equal length; an array without elements is a single empty row
     Array(Vec<Vec<f64>>),
+    Complex(Complex),
+    Integer(BigInt),
+    // never a whole number; those are Integer
+    Rational(Rational),
+    // fixed-width binary integer for programmer work
+    Word(Word),
+    // a real value carrying a physical unit
+    Quantity(Quantity),
+    String(String),
+    // calendar date, optionally with a time of day
+    Date(Date),
+    Duration(Duration),
+}
+
+// The type of a stack item without its value, used to describe and check function arguments
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum ItemKind {
+    Number,
+    Array,
+    Complex,
+    Integer,
+    Rational,
+    Word,
+    Quantity,
+    String,
+    Date,
+    Duration,
 }
 
-impl Clone for StackItem {
-    fn clone(&self) -> Self {
+impl ItemKind {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ItemKind::Number => "number",
+            ItemKind::Array => "array",
+            ItemKind::Complex => "complex",
+            ItemKind::Integer => "integer",
+            ItemKind::Rational => "rational",
+            ItemKind::Word => "binary",
+            ItemKind::Quantity => "quantity",
+            ItemKind::String => "string",
+            ItemKind::Date => "date",
+            ItemKind::Duration => "duration",
+        }
+    }
+}
+
+impl StackItem {
+    pub(crate) fn kind(&self) -> ItemKind {
+        match self {
+            StackItem::Number(_) => ItemKind::Number,
+            StackItem::Array(_) => ItemKind::Array,
+            StackItem::Complex(_) => ItemKind::Complex,
+            StackItem::Integer(_) => ItemKind::Integer,
+            StackItem::Rational(_) => ItemKind::Rational,
+            StackItem::Word(_) => ItemKind::Word,
+            StackItem::Quantity(_) => ItemKind::Quantity,
+            StackItem::String(_) => ItemKind::String,
+            StackItem::Date(_) => ItemKind::Date,
+            StackItem::Duration(_) => ItemKind::Duration,
+        }
+    }
+
+    // Wraps an exact result, keeping whole numbers as integers
+    pub(crate) fn from_exact(value: Rational) -> StackItem {
+        if value.is_integer() {
+            StackItem::Integer(value.numerator().clone())
+        } else {
+            StackItem::Rational(value)
+        }
+    }
+
+    // Any real scalar as a float; exact values are rounded to the nearest float
+    pub(crate) fn as_real(&self) -> Option<f64> {
+        match self {
+            StackItem::Number(num) => Some(*num),
+            StackItem::Integer(int) => Some(int.to_f64()),
+            StackItem::Rational(rational) => Some(rational.to_f64()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_exact(&self) -> Option<Rational> {
+        match self {
+            StackItem::Integer(int) => Some(Rational::from(int.clone())),
+            StackItem::Rational(rational) => Some(rational.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_number(&self) -> Result<f64, String> {
+        self.as_real().ok_or_else(|| format!("Expected a number, got {}", self.kind().name()))
+    }
+
+    // Real numbers are complex numbers with no imaginary part
+    pub(crate) fn as_complex(&self) -> Result<Complex, String> {
+        match self {
+            StackItem::Complex(c) => Ok(*c),
+            other => match other.as_real() {
+                Some(num) => Ok(Complex::from(num)),
+                None => Err(format!("Expected a complex number, got {}", other.kind().name())),
+            },
+        }
+    }
+
+    // Whole numbers wrapped into a word of the given shape; words are resized
+    pub(crate) fn to_word(&self, size: u32, signed: bool) -> Result<Word, String> {
+        match self {
+            StackItem::Word(word) => Ok(word.resized(size, signed)),
+            StackItem::Integer(int) => Ok(Word::new(int.to_u64_wrapping(), size, signed)),
+            StackItem::Number(num) if num.fract() == 0.0 => Ok(Word::from_i128(*num as i128, size, signed)),
+            other => Err(format!("Expected a whole number, got {}", other.kind().name())),
+        }
+    }
+
+    pub(crate) fn into_array(self) -> Result<Vec<Vec<f64>>, String> {
+        match self {
+            StackItem::Array(arr) => Ok(arr),
+            other => Err(format!("Expected an array, got {}", other.kind().name())),
+        }
+    }
+
+    // Input text that parses back to exactly this item, whatever the display settings.
+    // Floats use their shortest round-trip form with a decimal point or exponent, so
+    // they don't come back as integers; arrays are bracketed so [5.0] stays an array.
+    pub(crate) fn to_literal(&self) -> String {
         match self {
-            StackItem::Number(num) => StackItem::Number(*num),
-            StackItem::Array(arr) => StackItem::Array(arr.clone()),
-            // Add cases for other variants of StackItem
+            StackItem::Number(num) => format!("{:?}", num),
+            StackItem::Array(arr) => {
+                let rows: Vec<String> = arr.iter().map(|row| {
+                    row.iter().map(|n| format!("{:?}", n)).collect::<Vec<String>>().join(" ")
+                }).collect();
+                format!("[{}]", rows.join("; "))
+            },
+            StackItem::Complex(c) => format!("({:?},{:?})", c.re, c.im),
+            StackItem::Integer(int) => int.to_string(),
+            StackItem::Rational(rational) => rational.to_string(),
+            StackItem::Word(word) => word.to_literal(),
+            StackItem::Quantity(quantity) => format!("{:?}_{}", quantity.value, quantity.unit),
+            StackItem::String(text) => format_string_literal(text),
+            StackItem::Date(date) => date.to_string(),
+            StackItem::Duration(duration) => duration.to_string(),
+        }
+    }
+
+    // A JSON object tagged with the kind name. Big integers and word bits are strings
+    // so JSON readers that only have doubles don't round them.
+    pub(crate) fn to_json(&self) -> Json {
+        let kind = ("type", Json::text(self.kind().name()));
+        match self {
+            StackItem::Number(num) => Json::object(vec![kind, ("value", Json::float(*num))]),
+            StackItem::Array(arr) => {
+                let rows = arr.iter().map(|row| Json::Array(row.iter().map(|n| Json::float(*n)).collect())).collect();
+                Json::object(vec![kind, ("rows", Json::Array(rows))])
+            },
+            StackItem::Complex(c) => Json::object(vec![kind, ("re", Json::float(c.re)), ("im", Json::float(c.im))]),
+            StackItem::Integer(int) => Json::object(vec![kind, ("value", Json::text(int.to_string()))]),
+            StackItem::Rational(rational) => Json::object(vec![
+                kind,
+                ("numerator", Json::text(rational.numerator().to_string())),
+                ("denominator", Json::text(rational.denominator().to_string())),
+            ]),
+            StackItem::Word(word) => Json::object(vec![
+                kind,
+                ("bits", Json::text(word.bits().to_string())),
+                ("size", Json::Number(word.size().to_string())),
+                ("signed", Json::Bool(word.signed())),
+            ]),
+            StackItem::Quantity(quantity) => Json::object(vec![
+                kind,
+                ("value", Json::float(quantity.value)),
+                ("unit", Json::text(quantity.unit.to_string())),
+            ]),
+            StackItem::String(text) => Json::object(vec![kind, ("value", Json::text(text.clone()))]),
+            StackItem::Date(date) => Json::object(vec![kind, ("value", Json::text(date.to_string()))]),
+            StackItem::Duration(duration) => Json::object(vec![kind, ("value", Json::text(duration.to_string()))]),
+        }
+    }
+
+    // Reads back what to_json writes
+    pub(crate) fn from_json(json: &Json) -> Result<StackItem, String> {
+        let value = || json.get("value");
+        let integer = |key: &str| -> Result<BigInt, String> {
+            let text = json.get(key)?.as_str()?;
+            BigInt::parse(text).ok_or_else(|| format!("Invalid integer: {}", text))
+        };
+        let kind = json.get("type")?.as_str()?;
+        match kind {
+            "number" => Ok(StackItem::Number(value()?.as_f64()?)),
+            "array" => {
+                let rows = json.get("rows")?.as_array()?.iter()
+                    .map(|row| row.as_array()?.iter().map(Json::as_f64).collect::<Result<Vec<f64>, String>>())
+                    .collect::<Result<Vec<Vec<f64>>, String>>()?;
+                if rows.is_empty() {
+                    return Err("An array needs at least one row".to_string());
+                }
+                if rows.iter().any(|row| row.len() != rows[0].len()) {
+                    return Err("Array rows must all have the same number of elements".to_string());
+                }
+                Ok(StackItem::Array(rows))
+            },
+            "complex" => Ok(StackItem::Complex(Complex::new(json.get("re")?.as_f64()?, json.get("im")?.as_f64()?))),
+            "integer" => Ok(StackItem::Integer(integer("value")?)),
+            "rational" => Ok(StackItem::from_exact(Rational::new(integer("numerator")?, integer("denominator")?)?)),
+            "binary" => {
+                let bits = json.get("bits")?.as_str()?;
+                let bits = bits.parse::<u64>().map_err(|_| format!("Invalid binary integer: {}", bits))?;
+                let size = json.get("size")?.as_f64()? as u32;
+                if !WORD_SIZES.contains(&size) {
+                    return Err(format!("Invalid word size: {}", size));
+                }
+                Ok(StackItem::Word(Word::new(bits, size, json.get("signed")?.as_bool()?)))
+            },
+            "quantity" => Ok(StackItem::Quantity(Quantity::new(
+                value()?.as_f64()?,
+                Unit::parse(json.get("unit")?.as_str()?)?,
+            ))),
+            "string" => Ok(StackItem::String(value()?.as_str()?.to_string())),
+            "date" => {
+                let text = value()?.as_str()?;
+                Ok(StackItem::Date(Date::parse(text).ok_or_else(|| format!("Invalid date: {}", text))??))
+            },
+            "duration" => {
+                let text = value()?.as_str()?;
+                Ok(StackItem::Duration(Duration::parse(text).ok_or_else(|| format!("Invalid duration: {}", text))??))
+            },
+            other => Err(format!("Unknown item type: {}", other)),
         }
     }
 }
@@ -17,35 +247,428 @@ impl std::ops::Mul for StackItem {
     type Output = Result<StackItem, String>;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (StackItem::Number(a), StackItem::Number(b)) => Ok(StackItem::Number(a * b)),
-            (StackItem::Number(scalar), StackItem::Array(arr)) | (StackItem::Array(arr), StackItem::Number(scalar)) => {
-                let result = arr.into_iter()
-                    .map(|row| row.into_iter().map(|x| x * scalar).collect())
-                    .collect();
-                Ok(StackItem::Array(result))
-            },
-            (StackItem::Array(a), StackItem::Array(b)) => {
-                if a.is_empty() || b.is_empty() {
-                    return Err("Cannot multiply empty arrays".to_string());
+        binary(self, rhs, BinaryOp::Mul)
+    }
+}
+
+impl std::ops::Add for StackItem {
+    type Output = Result<StackItem, String>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        binary(self, rhs, BinaryOp::Add)
+    }
+}
+
+impl std::ops::Sub for StackItem {
+    type Output = Result<StackItem, String>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        binary(self, rhs, BinaryOp::Sub)
+    }
+}
+
+impl std::ops::Div for StackItem {
+    type Output = Result<StackItem, String>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        binary(self, rhs, BinaryOp::Div)
+    }
+}
+
+impl StackItem {
+    // There is no operator trait for exponentiation, so power lives here
+    pub(crate) fn pow(self, rhs: Self) -> Result<StackItem, String> {
+        binary(self, rhs, BinaryOp::Pow)
+    }
+
+    pub(crate) fn modulo(self, rhs: Self) -> Result<StackItem, String> {
+        binary(self, rhs, BinaryOp::Mod)
+    }
+
+    // Applies a function to a number, a complex number or every element of an array.
+    // `real` returns None where the result is not real (e.g. the square root of a
+    // negative number); numbers are then promoted to complex, array elements are an error.
+    pub(crate) fn map_math<R, C>(self, name: &str, real: R, complex: C) -> Result<StackItem, String>
+    where
+        R: Fn(f64) -> Option<f64>,
+        C: Fn(Complex) -> Complex,
+    {
+        match self {
+            StackItem::Complex(c) => Ok(StackItem::Complex(complex(c))),
+            StackItem::Array(arr) => map_array(arr, |x| {
+                real(x).ok_or_else(|| format!("{} of array element {} is not real", name, x))
+            }),
+            other => {
+                let num = other.as_number()?;
+                match real(num) {
+                    Some(result) => Ok(StackItem::Number(result)),
+                    None => Ok(StackItem::Complex(complex(Complex::from(num)))),
+                }
+            },
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub(crate) enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Mod,
+}
+
+// Exact powers are only attempted while the result stays small enough for the
+// schoolbook multiply to finish without stalling the interface
+const MAX_EXACT_POWER_BITS: usize = 1 << 16;
+
+impl BinaryOp {
+    fn name(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "addition",
+            BinaryOp::Sub => "subtraction",
+            BinaryOp::Mul => "multiplication",
+            BinaryOp::Div => "division",
+            BinaryOp::Pow => "power",
+            BinaryOp::Mod => "modulo",
+        }
+    }
+
+    fn real(&self, a: f64, b: f64) -> Result<f64, String> {
+        match self {
+            BinaryOp::Add => Ok(a + b),
+            BinaryOp::Sub => Ok(a - b),
+            BinaryOp::Mul => Ok(a * b),
+            BinaryOp::Div if b == 0.0 => Err("Division by zero".to_string()),
+            BinaryOp::Div => Ok(a / b),
+            BinaryOp::Pow => Ok(a.powf(b)),
+            BinaryOp::Mod if b == 0.0 => Err("Division by zero".to_string()),
+            BinaryOp::Mod => Ok(a - b * (a / b).floor()),
+        }
+    }
+
+    fn complex(&self, a: Complex, b: Complex) -> Result<Complex, String> {
+        match self {
+            BinaryOp::Add => Ok(a + b),
+            BinaryOp::Sub => Ok(a - b),
+            BinaryOp::Mul => Ok(a * b),
+            BinaryOp::Div => a.checked_div(b),
+            BinaryOp::Pow if a == Complex::from(0.0) && b.re == 0.0 && b.im != 0.0 => {
+                Err("Zero to an imaginary power is undefined".to_string())
+            },
+            BinaryOp::Pow => Ok(a.pow(b)),
+            BinaryOp::Mod => Err("Complex numbers have no modulo".to_string()),
+        }
+    }
+
+    // None when the result can't be exact (fractional or enormous powers)
+    fn exact(&self, a: &Rational, b: &Rational) -> Option<Result<Rational, String>> {
+        match self {
+            BinaryOp::Add => Some(Ok(a + b)),
+            BinaryOp::Sub => Some(Ok(a - b)),
+            BinaryOp::Mul => Some(Ok(a * b)),
+            BinaryOp::Div => Some(a.checked_div(b)),
+            BinaryOp::Mod => Some(a.modulo(b)),
+            BinaryOp::Pow => {
+                if !b.is_integer() {
+                    return None;
                 }
-                if a[0].len() != b.len() {
-                    return Err("Incompatible array dimensions for multiplication".to_string());
+                let exponent = b.numerator().to_i64()?;
+                let size = a.numerator().bit_length().max(a.denominator().bit_length());
+                if (size as u128) * (exponent.unsigned_abs() as u128) > MAX_EXACT_POWER_BITS as u128 {
+                    return None;
                 }
-                let result: Vec<Vec<f64>> = a.into_iter()
-                    .map(|row_a| {
-                        b[0].iter().enumerate()
-                            .map(|(i, _)| {
-                                row_a.iter()
-                                    .zip(b.iter().map(|row_b| row_b[i]))
-                                    .map(|(&x, y)| x * y)
-                                    .sum()
-                            })
-                            .collect()
-                    })
-                    .collect();
-                Ok(StackItem::Array(result))
+                Some(a.pow(exponent))
+            },
+        }
+    }
+}
+
+// Arithmetic across the numeric kinds. Integers and fractions stay exact for as
+// long as possible, anything mixed with a float becomes a float, and anything
+// mixed with a complex number becomes complex. Scalars broadcast over arrays and
+// arrays of the same shape combine element-wise (except *, the matrix product).
+pub(crate) fn binary(lhs: StackItem, rhs: StackItem, op: BinaryOp) -> Result<StackItem, String> {
+    // adding strings joins them
+    if let (StackItem::String(a), StackItem::String(b), BinaryOp::Add) = (&lhs, &rhs, op) {
+        return Ok(StackItem::String(format!("{}{}", a, b)));
+    }
+    if let (StackItem::Word(_), _) | (_, StackItem::Word(_)) = (&lhs, &rhs) {
+        return word_binary(&lhs, &rhs, op);
+    }
+    if let (StackItem::Quantity(_), _) | (_, StackItem::Quantity(_)) = (&lhs, &rhs) {
+        return quantity_binary(&lhs, &rhs, op);
+    }
+    if let (StackItem::Date(_) | StackItem::Duration(_), _) | (_, StackItem::Date(_) | StackItem::Duration(_)) = (&lhs, &rhs) {
+        return date_binary(&lhs, &rhs, op);
+    }
+    if let (Some(a), Some(b)) = (lhs.as_exact(), rhs.as_exact()) {
+        if let Some(result) = op.exact(&a, &b) {
+            return Ok(StackItem::from_exact(result?));
+        }
+    }
+
+    match (lhs, rhs) {
+        (StackItem::Array(a), StackItem::Array(b)) if op == BinaryOp::Mul => {
+            Ok(StackItem::Array(linalg::multiply(&a, &b)?))
+        },
+        (StackItem::Array(a), StackItem::Array(b)) => {
+            if shape(&a) != shape(&b) {
+                return Err(format!("Incompatible array dimensions for {}: {} and {}", op.name(), dims_text(&a), dims_text(&b)));
+            }
+            let result = a.into_iter().zip(b)
+                .map(|(row_a, row_b)| {
+                    row_a.into_iter().zip(row_b)
+                        .map(|(x, y)| op.real(x, y))
+                        .collect::<Result<Vec<f64>, String>>()
+                })
+                .collect::<Result<Vec<Vec<f64>>, String>>()?;
+            Ok(StackItem::Array(result))
+        },
+        (StackItem::Array(arr), scalar) => match scalar.as_real() {
+            Some(scalar) => map_array(arr, |x| op.real(x, scalar)),
+            None => Err(format!("Cannot apply {} to array and {}", op.name(), scalar.kind().name())),
+        },
+        (scalar, StackItem::Array(arr)) => match scalar.as_real() {
+            Some(scalar) => map_array(arr, |x| op.real(scalar, x)),
+            None => Err(format!("Cannot apply {} to {} and array", op.name(), scalar.kind().name())),
+        },
+        (lhs, rhs) => match (lhs.as_real(), rhs.as_real()) {
+            // a fractional power of a negative number has no real value
+            (Some(a), Some(b)) if op == BinaryOp::Pow && a < 0.0 && b.fract() != 0.0 => {
+                Ok(StackItem::Complex(Complex::from(a).pow(Complex::from(b))))
+            },
+            (Some(a), Some(b)) => Ok(StackItem::Number(op.real(a, b)?)),
+            _ => match (lhs.as_complex(), rhs.as_complex()) {
+                (Ok(a), Ok(b)) => Ok(StackItem::Complex(op.complex(a, b)?)),
+                _ => Err(format!("Cannot apply {} to {} and {}", op.name(), lhs.kind().name(), rhs.kind().name())),
             },
+        },
+    }
+}
+
+// Binary integers wrap at the word size. Mixed with other whole numbers the other
+// operand takes the word's shape; two words combine at the larger size.
+fn word_binary(lhs: &StackItem, rhs: &StackItem, op: BinaryOp) -> Result<StackItem, String> {
+    let (size, signed) = match (lhs, rhs) {
+        (StackItem::Word(a), StackItem::Word(b)) => (a.size().max(b.size()), a.signed() || b.signed()),
+        (StackItem::Word(word), _) | (_, StackItem::Word(word)) => (word.size(), word.signed()),
+        _ => unreachable!(),
+    };
+    let wrong_kinds = |_| format!("Cannot apply {} to {} and {}", op.name(), lhs.kind().name(), rhs.kind().name());
+    let a = lhs.to_word(size, signed).map_err(wrong_kinds)?;
+    let b = rhs.to_word(size, signed).map_err(wrong_kinds)?;
+    let result = match op {
+        BinaryOp::Add => a.add(b),
+        BinaryOp::Sub => a.sub(b),
+        BinaryOp::Mul => a.mul(b),
+        BinaryOp::Div => a.div(b)?,
+        BinaryOp::Pow => a.pow(b)?,
+        BinaryOp::Mod => a.rem(b)?,
+    };
+    Ok(StackItem::Word(result))
+}
+
+// Units multiply and divide along with the values; addition and subtraction need
+// matching dimensions and give the result in the left operand's unit. Plain numbers
+// count as dimensionless, and a result whose units all cancel becomes a number.
+fn quantity_binary(lhs: &StackItem, rhs: &StackItem, op: BinaryOp) -> Result<StackItem, String> {
+    let wrong_kinds = || format!("Cannot apply {} to {} and {}", op.name(), lhs.kind().name(), rhs.kind().name());
+    let as_quantity = |item: &StackItem| match item {
+        StackItem::Quantity(quantity) => Some(quantity.clone()),
+        other => other.as_real().map(|num| Quantity::new(num, Unit::none())),
+    };
+    let a = as_quantity(lhs).ok_or_else(wrong_kinds)?;
+    let result = match op {
+        BinaryOp::Pow => {
+            let exponent = rhs.as_real().filter(|b| b.fract() == 0.0 && b.abs() <= i32::MAX as f64)
+                .ok_or_else(|| "Quantities can only be raised to whole number powers".to_string())?;
+            Quantity::new(a.value.powi(exponent as i32), a.unit.powi(exponent as i32))
+        },
+        BinaryOp::Mul | BinaryOp::Div => {
+            let b = as_quantity(rhs).ok_or_else(wrong_kinds)?;
+            let value = op.real(a.value, b.value)?;
+            let unit = if op == BinaryOp::Mul { a.unit.mul(&b.unit) } else { a.unit.div(&b.unit) };
+            Quantity::new(value, unit)
+        },
+        BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mod => {
+            let b = as_quantity(rhs).ok_or_else(wrong_kinds)?.convert(&a.unit)?;
+            Quantity::new(op.real(a.value, b.value)?, a.unit)
+        },
+    };
+    if result.is_plain_number() {
+        Ok(StackItem::Number(result.si_value()))
+    } else {
+        Ok(StackItem::Quantity(result))
+    }
+}
+
+// Schedule arithmetic. The difference of two dates is a duration, durations
+// shift dates, and a plain number added to a date counts days.
+fn date_binary(lhs: &StackItem, rhs: &StackItem, op: BinaryOp) -> Result<StackItem, String> {
+    const DAY: f64 = 86400.0;
+    let seconds = |value: f64| -> Result<i64, String> {
+        let rounded = value.round();
+        if rounded.abs() >= i64::MAX as f64 || rounded.is_nan() {
+            return Err("Duration out of range".to_string());
+        }
+        Ok(rounded as i64)
+    };
+    // moves a date forward by `shift` seconds, or back when subtracting
+    let shift_date = |date: &Date, shift: i64| -> Result<StackItem, String> {
+        let shift = if op == BinaryOp::Sub { shift.checked_neg() } else { Some(shift) };
+        let seconds = shift.and_then(|shift| date.seconds().checked_add(shift)).ok_or("Date out of range")?;
+        Ok(StackItem::Date(Date::from_seconds(seconds, date.is_date_only())?))
+    };
+    let result = match (lhs, rhs, op) {
+        (StackItem::Date(a), StackItem::Date(b), BinaryOp::Sub) => {
+            let difference = a.seconds().checked_sub(b.seconds()).ok_or("Duration out of range")?;
+            StackItem::Duration(Duration::from_seconds(difference))
+        },
+        (StackItem::Date(date), StackItem::Duration(duration), BinaryOp::Add | BinaryOp::Sub)
+        | (StackItem::Duration(duration), StackItem::Date(date), BinaryOp::Add) => {
+            shift_date(date, duration.seconds())?
+        },
+        (StackItem::Date(date), days, BinaryOp::Add | BinaryOp::Sub) | (days, StackItem::Date(date), BinaryOp::Add)
+            if days.as_real().is_some() =>
+        {
+            shift_date(date, seconds(days.as_number()? * DAY)?)?
+        },
+        (StackItem::Duration(a), StackItem::Duration(b), BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mod) => {
+            let value = op.real(a.seconds() as f64, b.seconds() as f64)?;
+            StackItem::Duration(Duration::from_seconds(seconds(value)?))
+        },
+        (StackItem::Duration(a), StackItem::Duration(b), BinaryOp::Div) => {
+            StackItem::Number(op.real(a.seconds() as f64, b.seconds() as f64)?)
+        },
+        (StackItem::Duration(duration), factor, BinaryOp::Mul | BinaryOp::Div) | (factor, StackItem::Duration(duration), BinaryOp::Mul)
+            if factor.as_real().is_some() =>
+        {
+            let value = op.real(duration.seconds() as f64, factor.as_number()?)?;
+            StackItem::Duration(Duration::from_seconds(seconds(value)?))
+        },
+        _ => return Err(format!("Cannot apply {} to {} and {}", op.name(), lhs.kind().name(), rhs.kind().name())),
+    };
+    Ok(result)
+}
+
+fn map_array<F>(arr: Vec<Vec<f64>>, op: F) -> Result<StackItem, String>
+where
+    F: Fn(f64) -> Result<f64, String>,
+{
+    let result = arr.into_iter()
+        .map(|row| row.into_iter().map(&op).collect::<Result<Vec<f64>, String>>())
+        .collect::<Result<Vec<Vec<f64>>, String>>()?;
+    Ok(StackItem::Array(result))
+}
+
+pub(crate) fn dims_text(arr: &[Vec<f64>]) -> String {
+    let (rows, cols) = shape(arr);
+    format!("{}x{}", rows, cols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::flow::value_to_item;
+    use crate::control::parsing::{InputType, ParsedInput};
+    use crate::data::settings::WordSettings;
+
+    // One or more values of every kind, including the awkward ones
+    fn samples() -> Vec<StackItem> {
+        let quantity = |text: &str| StackItem::Quantity(Quantity::parse(text).unwrap().unwrap());
+        let date = |text: &str| StackItem::Date(Date::parse(text).unwrap().unwrap());
+        let duration = |text: &str| StackItem::Duration(Duration::parse(text).unwrap().unwrap());
+        vec![
+            StackItem::Number(3.0),
+            StackItem::Number(0.1),
+            StackItem::Number(-1e300),
+            StackItem::Number(5e-324),
+            StackItem::Number(-0.0),
+            StackItem::Number(f64::INFINITY),
+            StackItem::Number(f64::NEG_INFINITY),
+            StackItem::Array(vec![vec![5.0]]),
+            StackItem::Array(vec![vec![1.5, -2.0], vec![3e30, f64::INFINITY]]),
+            StackItem::Array(vec![vec![]]),
+            StackItem::Complex(Complex::new(1.0, -2.5)),
+            StackItem::Complex(Complex::new(0.0, 1e-20)),
+            StackItem::Integer(BigInt::parse("-123456789012345678901234567890").unwrap()),
+            StackItem::Integer(BigInt::zero()),
+            StackItem::from_exact(Rational::parse("-3/7").unwrap().unwrap()),
+            StackItem::Word(Word::new(0xFF, 8, true)),
+            StackItem::Word(Word::new(u64::MAX, 64, false)),
+            StackItem::Word(Word::new(0, 16, false)),
+            quantity("9.81_m/s^2"),
+            quantity("2_deg"),
+            (quantity("5_1/s") * quantity("2_m")).unwrap(),
+            (quantity("3_kg") * quantity("2_m^2/s^2")).unwrap(),
+            StackItem::String("a \"quoted\"\n\\ \t line".to_string()),
+            StackItem::String(String::new()),
+            StackItem::String("  é 😀 \u{1}".to_string()),
+            date("2024-02-29"),
+            date("0000-01-01"),
+            date("9999-12-31T23:59:59"),
+            duration("-100:30:05"),
+            duration("00:00"),
+        ]
+    }
+
+    fn parse_literal(text: &str) -> StackItem {
+        match ParsedInput::create_from_buf(text.to_string()).unwrap().input_type {
+            // the word settings differ from every sample so sizes must come from the literal
+            InputType::Value(value) => value_to_item(value, &WordSettings { size: 32, signed: true }),
+            _ => panic!("{} is not a value", text),
+        }
+    }
+
+    // NaN never equals itself, so compare the literal text instead
+    fn same(a: &StackItem, b: &StackItem) -> bool {
+        a == b || a.to_literal() == b.to_literal()
+    }
+
+    #[test]
+    fn literals_parse_back_to_the_same_item() {
+        let mut items = samples();
+        items.push(StackItem::Number(f64::NAN));
+        for item in items {
+            let literal = item.to_literal();
+            let parsed = parse_literal(&literal);
+            assert!(same(&parsed, &item), "{} read back as {:?}, not {:?}", literal, parsed, item);
+        }
+    }
+
+    #[test]
+    fn json_reads_back_to_the_same_item() {
+        let mut items = samples();
+        items.push(StackItem::Number(f64::NAN));
+        for item in items {
+            let json = item.to_json().to_string();
+            let parsed = StackItem::from_json(&Json::parse(&json).unwrap()).unwrap();
+            assert!(same(&parsed, &item), "{} read back as {:?}, not {:?}", json, parsed, item);
+        }
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected_when_created() {
+        let last_day = StackItem::Date(Date::parse("9999-12-31").unwrap().unwrap());
+        assert!((last_day.clone() + StackItem::Number(1.0)).is_err());
+        assert!((last_day - StackItem::Number(1.0)).is_ok());
+        let first_day = StackItem::Date(Date::parse("0000-01-01").unwrap().unwrap());
+        assert!((first_day - StackItem::Number(1.0)).is_err());
+        assert!(StackItem::from_json(&Json::parse(r#"{"type":"array","rows":[]}"#).unwrap()).is_err());
+        assert_eq!(linalg::transpose(&[vec![]]), vec![Vec::<f64>::new()]);
+    }
+
+    #[test]
+    fn malformed_json_is_an_error() {
+        let nested = "[".repeat(100_000) + &"]".repeat(100_000);
+        assert!(Json::parse(&nested).is_err());
+        assert!(Json::parse(&"[".repeat(64)).is_err());
+        assert!(Json::parse(&("[".repeat(64) + &"]".repeat(64))).is_ok());
+        for text in ["[1,]", "{\"a\":1} x", "\"open", "{\"type\":\"binary\",\"bits\":\"1\",\"size\":7,\"signed\":true}"] {
+            let result = Json::parse(text).and_then(|json| StackItem::from_json(&json));
+            assert!(result.is_err(), "{}", text);
         }
     }
 }
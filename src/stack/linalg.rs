@@ -0,0 +1,267 @@
+use crate::data::context::AppContext;
+use crate::math::{decomp, linalg};
+use crate::stack::functions::count_argument;
+use crate::stack::item::{ItemKind, StackItem};
+use crate::stack::registry::FunctionSpec;
+use crate::stack::transaction::pop_operands;
+
+const ARRAY: &[ItemKind] = &[ItemKind::Array];
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "transpose",
+        aliases: &["trn"],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Transpose the matrix on level 1",
+        handler: transpose,
+    },
+    FunctionSpec {
+        name: "det",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Determinant of a square matrix",
+        handler: det,
+    },
+    FunctionSpec {
+        name: "inv",
+        aliases: &["inverse"],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Inverse of a square, non-singular matrix",
+        handler: inv,
+    },
+    FunctionSpec {
+        name: "trace",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Sum of the diagonal of a square matrix",
+        handler: trace,
+    },
+    FunctionSpec {
+        name: "rank",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Number of linearly independent rows",
+        handler: rank,
+    },
+    FunctionSpec {
+        name: "solve",
+        aliases: &[],
+        arity: 2,
+        accepts: &[ARRAY, ARRAY],
+        description: "Solve A x = b with A on level 2 and b on level 1",
+        handler: solve,
+    },
+    FunctionSpec {
+        name: "ident",
+        aliases: &["idn", "eye"],
+        arity: 0,
+        accepts: &[],
+        description: "Push the n x n identity matrix (n from arg or level 1)",
+        handler: ident,
+    },
+    FunctionSpec {
+        name: "mpow",
+        aliases: &[],
+        arity: 2,
+        accepts: &[&[ItemKind::Number, ItemKind::Integer], ARRAY],
+        description: "Raise the matrix on level 2 to the integer power on level 1",
+        handler: mpow,
+    },
+    FunctionSpec {
+        name: "lu",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "LU with partial pivoting: push L, U and P where P A = L U",
+        handler: lu,
+    },
+    FunctionSpec {
+        name: "qr",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "QR decomposition: push Q and R where A = Q R",
+        handler: qr,
+    },
+    FunctionSpec {
+        name: "chol",
+        aliases: &["cholesky"],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Cholesky factor L of a positive definite matrix, A = L L^T",
+        handler: chol,
+    },
+    FunctionSpec {
+        name: "eigsym",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Symmetric eigendecomposition: push eigenvalues (ascending) and eigenvectors",
+        handler: eigsym,
+    },
+    FunctionSpec {
+        name: "eig",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "General eigendecomposition: push eigenvalues as [re im] rows and V",
+        handler: eig,
+    },
+    FunctionSpec {
+        name: "svd",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Singular value decomposition: push U, S and V where A = U S V^T",
+        handler: svd,
+    },
+    FunctionSpec {
+        name: "svals",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Singular values in descending order",
+        handler: svals,
+    },
+];
+
+fn pop_array(context: &mut AppContext) -> Result<Vec<Vec<f64>>, String> {
+    pop_operands(&mut context.stack, 1)?.pop().unwrap().into_array()
+}
+
+fn transpose(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    context.stack.push(StackItem::Array(linalg::transpose(&a)));
+    Ok(())
+}
+
+fn det(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    context.stack.push(StackItem::Number(linalg::determinant(&a)?));
+    Ok(())
+}
+
+fn inv(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    context.stack.push(StackItem::Array(linalg::inverse(&a)?));
+    Ok(())
+}
+
+fn trace(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    context.stack.push(StackItem::Number(linalg::trace(&a)?));
+    Ok(())
+}
+
+fn rank(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    context.stack.push(StackItem::Number(linalg::rank(&a) as f64));
+    Ok(())
+}
+
+// b may be entered as a row ("1 2 3") for convenience; the solution then comes back as a row too
+fn solve(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let b = pop_array(context)?;
+    let a = pop_array(context)?;
+    let (n, _) = linalg::shape(&a);
+    let (b_rows, b_cols) = linalg::shape(&b);
+    let x = if b_rows == 1 && b_cols == n && n > 1 {
+        linalg::transpose(&linalg::solve(&a, &linalg::transpose(&b))?)
+    } else {
+        linalg::solve(&a, &b)?
+    };
+    context.stack.push(StackItem::Array(x));
+    Ok(())
+}
+
+fn ident(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let n = count_argument(&args, context)?;
+    linalg::check_dimension(n)?;
+    context.stack.push(StackItem::Array(linalg::identity(n)));
+    Ok(())
+}
+
+fn mpow(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let exponent = pop_operands(&mut context.stack, 1)?.pop().unwrap().as_number()?;
+    if exponent.fract() != 0.0 {
+        return Err("Exponent must be an integer".to_string());
+    }
+    let a = pop_array(context)?;
+    context.stack.push(StackItem::Array(linalg::power(&a, exponent as i64)?));
+    Ok(())
+}
+
+fn lu(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    let (l, u, p) = decomp::lu(&a)?;
+    context.stack.extend([StackItem::Array(l), StackItem::Array(u), StackItem::Array(p)]);
+    Ok(())
+}
+
+fn qr(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    let (q, r) = decomp::qr(&a)?;
+    context.stack.extend([StackItem::Array(q), StackItem::Array(r)]);
+    Ok(())
+}
+
+fn chol(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    context.stack.push(StackItem::Array(decomp::cholesky(&a)?));
+    Ok(())
+}
+
+fn eigsym(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    let (values, vectors) = decomp::eigen_symmetric(&a)?;
+    context.stack.extend([StackItem::Array(vec![values]), StackItem::Array(vectors)]);
+    Ok(())
+}
+
+// Complex eigenvalues can't live in a real array, so each eigenvalue is a [re im] row.
+// Conjugate pairs share a pair of columns in V (real part, then imaginary part).
+fn eig(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    let (real, imaginary, vectors) = decomp::eigen_general(&a)?;
+    let values = real.into_iter().zip(imaginary).map(|(re, im)| vec![re, im]).collect();
+    context.stack.extend([StackItem::Array(values), StackItem::Array(vectors)]);
+    Ok(())
+}
+
+fn svd(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    let (u, s, v) = decomp::svd(&a)?;
+    context.stack.extend([StackItem::Array(u), StackItem::Array(s), StackItem::Array(v)]);
+    Ok(())
+}
+
+fn svals(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let a = pop_array(context)?;
+    let (_, s, _) = decomp::svd(&a)?;
+    let values = (0..s.len()).map(|i| s[i][i]).collect();
+    context.stack.push(StackItem::Array(vec![values]));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::context::AppContext;
+    use crate::stack::functions::route_function_call;
+    use crate::stack::item::StackItem;
+
+    #[test]
+    fn ident_rejects_sizes_out_of_range() {
+        let mut context = AppContext::default();
+        for size in ["0", "1001", "1000000"] {
+            assert!(route_function_call("ident".to_string(), vec![size.to_string()], &mut context).is_err());
+        }
+        assert!(context.stack.is_empty());
+        route_function_call("ident".to_string(), vec!["2".to_string()], &mut context).unwrap();
+        assert_eq!(context.stack, vec![StackItem::Array(vec![vec![1.0, 0.0], vec![0.0, 1.0]])]);
+    }
+}
@@ -0,0 +1,388 @@
+use std::cmp::Ordering;
+use crate::data::context::AppContext;
+use crate::math::linalg::shape;
+use crate::math::rational::Rational;
+use crate::stack::item::{dims_text, ItemKind, StackItem};
+use crate::stack::registry::{FunctionSpec, ANY};
+use crate::stack::transaction::pop_operands;
+
+// Booleans are numbers by convention: comparisons give exact 0 or 1 for scalars
+// and 0/1 masks for arrays, and anything non-zero counts as true.
+
+pub(crate) const LOGICAL: &[ItemKind] = &[
+    ItemKind::Number, ItemKind::Integer, ItemKind::Rational, ItemKind::Array, ItemKind::Word,
+];
+const MASKABLE: &[ItemKind] = &[ItemKind::Number, ItemKind::Integer, ItemKind::Rational, ItemKind::Array];
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "eq",
+        aliases: &["=="],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "1 if level 2 equals level 1, else 0 (element-wise on arrays)",
+        handler: eq,
+    },
+    FunctionSpec {
+        name: "ne",
+        aliases: &["!="],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "1 if level 2 differs from level 1, else 0 (element-wise on arrays)",
+        handler: ne,
+    },
+    FunctionSpec {
+        name: "lt",
+        aliases: &["<"],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "1 if level 2 is less than level 1, else 0 (element-wise on arrays)",
+        handler: lt,
+    },
+    FunctionSpec {
+        name: "le",
+        aliases: &["<="],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "1 if level 2 is at most level 1, else 0 (element-wise on arrays)",
+        handler: le,
+    },
+    FunctionSpec {
+        name: "gt",
+        aliases: &[">"],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "1 if level 2 is greater than level 1, else 0 (element-wise on arrays)",
+        handler: gt,
+    },
+    FunctionSpec {
+        name: "ge",
+        aliases: &[">="],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "1 if level 2 is at least level 1, else 0 (element-wise on arrays)",
+        handler: ge,
+    },
+    FunctionSpec {
+        name: "select",
+        aliases: &["where", "ifte"],
+        arity: 3,
+        accepts: &[MASKABLE, MASKABLE, MASKABLE],
+        description: "Level 2 where the level 3 mask is true, level 1 elsewhere",
+        handler: select,
+    },
+    FunctionSpec {
+        name: "filter",
+        aliases: &[],
+        arity: 2,
+        accepts: &[&[ItemKind::Array], &[ItemKind::Array]],
+        description: "Row of the level 2 elements where the level 1 mask is true",
+        handler: filter,
+    },
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    // None means the operands are unordered (NaN, or different kinds)
+    fn holds(&self, ordering: Option<Ordering>) -> bool {
+        match (self, ordering) {
+            (Comparison::Ne, None) => true,
+            (_, None) => false,
+            (Comparison::Eq, Some(ordering)) => ordering == Ordering::Equal,
+            (Comparison::Ne, Some(ordering)) => ordering != Ordering::Equal,
+            (Comparison::Lt, Some(ordering)) => ordering == Ordering::Less,
+            (Comparison::Le, Some(ordering)) => ordering != Ordering::Greater,
+            (Comparison::Gt, Some(ordering)) => ordering == Ordering::Greater,
+            (Comparison::Ge, Some(ordering)) => ordering != Ordering::Less,
+        }
+    }
+
+    fn is_equality(&self) -> bool {
+        matches!(self, Comparison::Eq | Comparison::Ne)
+    }
+}
+
+fn truth(value: bool) -> StackItem {
+    StackItem::Integer(i64::from(value).into())
+}
+
+fn mask(value: bool) -> f64 {
+    if value { 1.0 } else { 0.0 }
+}
+
+fn exact_value(item: &StackItem) -> Option<Rational> {
+    match item {
+        StackItem::Word(word) => Some(Rational::from(word.to_bigint())),
+        other => other.as_exact(),
+    }
+}
+
+fn ordering(lhs: &StackItem, rhs: &StackItem, comparison: Comparison) -> Result<Option<Ordering>, String> {
+    if let (Some(a), Some(b)) = (exact_value(lhs), exact_value(rhs)) {
+        return Ok(Some(a.cmp(&b)));
+    }
+    match (lhs, rhs) {
+        (StackItem::String(a), StackItem::String(b)) => return Ok(Some(a.cmp(b))),
+        (StackItem::Date(a), StackItem::Date(b)) => return Ok(Some(a.seconds().cmp(&b.seconds()))),
+        (StackItem::Duration(a), StackItem::Duration(b)) => return Ok(Some(a.seconds().cmp(&b.seconds()))),
+        (StackItem::Quantity(a), StackItem::Quantity(b)) => {
+            return Ok(a.value.partial_cmp(&b.convert(&a.unit)?.value));
+        },
+        _ => {},
+    }
+    if let (Some(a), Some(b)) = (lhs.as_real(), rhs.as_real()) {
+        return Ok(a.partial_cmp(&b));
+    }
+    if let (Ok(a), Ok(b)) = (lhs.as_complex(), rhs.as_complex()) {
+        if !comparison.is_equality() {
+            return Err("Complex numbers have no ordering".to_string());
+        }
+        return Ok((a.re == b.re && a.im == b.im).then_some(Ordering::Equal));
+    }
+    if comparison.is_equality() {
+        return Ok((lhs == rhs).then_some(Ordering::Equal));
+    }
+    Err(format!("Cannot compare {} and {}", lhs.kind().name(), rhs.kind().name()))
+}
+
+fn compare(context: &mut AppContext, comparison: Comparison) -> Result<(), String> {
+    let mut operands = pop_operands(&mut context.stack, 2)?;
+    let rhs = operands.pop().unwrap();
+    let lhs = operands.pop().unwrap();
+    let test = |a: f64, b: f64| mask(comparison.holds(a.partial_cmp(&b)));
+    let result = match (&lhs, &rhs) {
+        (StackItem::Array(a), StackItem::Array(b)) => {
+            if shape(a) != shape(b) {
+                return Err(format!("Incompatible array dimensions: {} and {}", dims_text(a), dims_text(b)));
+            }
+            StackItem::Array(zip_arrays(a, b, test))
+        },
+        (StackItem::Array(arr), scalar) => {
+            let b = scalar.as_number()?;
+            StackItem::Array(map_elements(arr, |a| test(a, b)))
+        },
+        (scalar, StackItem::Array(arr)) => {
+            let a = scalar.as_number()?;
+            StackItem::Array(map_elements(arr, |b| test(a, b)))
+        },
+        _ => truth(comparison.holds(ordering(&lhs, &rhs, comparison)?)),
+    };
+    context.stack.push(result);
+    Ok(())
+}
+
+fn map_elements<F: Fn(f64) -> f64>(arr: &[Vec<f64>], op: F) -> Vec<Vec<f64>> {
+    arr.iter().map(|row| row.iter().map(|&x| op(x)).collect()).collect()
+}
+
+fn zip_arrays<F: Fn(f64, f64) -> f64>(a: &[Vec<f64>], b: &[Vec<f64>], op: F) -> Vec<Vec<f64>> {
+    a.iter().zip(b)
+        .map(|(row_a, row_b)| row_a.iter().zip(row_b).map(|(&x, &y)| op(x, y)).collect())
+        .collect()
+}
+
+fn eq(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    compare(context, Comparison::Eq)
+}
+
+fn ne(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    compare(context, Comparison::Ne)
+}
+
+fn lt(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    compare(context, Comparison::Lt)
+}
+
+fn le(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    compare(context, Comparison::Le)
+}
+
+fn gt(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    compare(context, Comparison::Gt)
+}
+
+fn ge(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    compare(context, Comparison::Ge)
+}
+
+// Logical and/or/xor on truth values; arrays combine element-wise into masks
+pub(crate) fn logical<F>(context: &mut AppContext, op: F) -> Result<(), String>
+where
+    F: Fn(bool, bool) -> bool,
+{
+    let mut operands = pop_operands(&mut context.stack, 2)?;
+    let rhs = operands.pop().unwrap();
+    let lhs = operands.pop().unwrap();
+    let apply = |a: f64, b: f64| mask(op(a != 0.0, b != 0.0));
+    let result = match (&lhs, &rhs) {
+        (StackItem::Array(a), StackItem::Array(b)) => {
+            if shape(a) != shape(b) {
+                return Err(format!("Incompatible array dimensions: {} and {}", dims_text(a), dims_text(b)));
+            }
+            StackItem::Array(zip_arrays(a, b, apply))
+        },
+        (StackItem::Array(arr), scalar) => {
+            let b = scalar.as_number()?;
+            StackItem::Array(map_elements(arr, |a| apply(a, b)))
+        },
+        (scalar, StackItem::Array(arr)) => {
+            let a = scalar.as_number()?;
+            StackItem::Array(map_elements(arr, |b| apply(a, b)))
+        },
+        _ => truth(op(lhs.as_number()? != 0.0, rhs.as_number()? != 0.0)),
+    };
+    context.stack.push(result);
+    Ok(())
+}
+
+pub(crate) fn logical_not(context: &mut AppContext) -> Result<(), String> {
+    let result = match pop_operands(&mut context.stack, 1)?.pop().unwrap() {
+        StackItem::Array(arr) => StackItem::Array(map_elements(&arr, |x| mask(x == 0.0))),
+        other => truth(other.as_number()? == 0.0),
+    };
+    context.stack.push(result);
+    Ok(())
+}
+
+// Element (i, j) of an array operand, or the operand itself if it is a scalar
+fn element(item: &StackItem, i: usize, j: usize) -> f64 {
+    match item {
+        StackItem::Array(arr) => arr[i][j],
+        other => other.as_real().unwrap_or(f64::NAN),
+    }
+}
+
+fn select(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let operands = pop_operands(&mut context.stack, 3)?;
+    let (condition, when_true, when_false) = (&operands[0], &operands[1], &operands[2]);
+    let StackItem::Array(mask) = condition else {
+        // a scalar condition picks one operand whole
+        let chosen = if condition.as_number()? != 0.0 { when_true } else { when_false };
+        context.stack.push(chosen.clone());
+        return Ok(());
+    };
+    for operand in [when_true, when_false] {
+        if let StackItem::Array(arr) = operand {
+            if shape(arr) != shape(mask) {
+                return Err(format!("Incompatible array dimensions: {} and {}", dims_text(mask), dims_text(arr)));
+            }
+        }
+    }
+    let result = mask.iter().enumerate()
+        .map(|(i, row)| row.iter().enumerate()
+            .map(|(j, &flag)| if flag != 0.0 { element(when_true, i, j) } else { element(when_false, i, j) })
+            .collect())
+        .collect();
+    context.stack.push(StackItem::Array(result));
+    Ok(())
+}
+
+fn filter(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let mut operands = pop_operands(&mut context.stack, 2)?;
+    let mask = operands.pop().unwrap().into_array()?;
+    let data = operands.pop().unwrap().into_array()?;
+    if shape(&data) != shape(&mask) {
+        return Err(format!("Incompatible array dimensions: {} and {}", dims_text(&data), dims_text(&mask)));
+    }
+    let selected = data.iter().flatten().zip(mask.iter().flatten())
+        .filter(|(_, &flag)| flag != 0.0)
+        .map(|(&x, _)| x)
+        .collect();
+    context.stack.push(StackItem::Array(vec![selected]));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::complex::Complex;
+    use crate::stack::functions::route_function_call;
+
+    fn run(stack: Vec<StackItem>, name: &str) -> Result<Vec<StackItem>, String> {
+        let mut context = AppContext { stack, ..Default::default() };
+        route_function_call(name.to_string(), Vec::new(), &mut context)?;
+        Ok(context.stack)
+    }
+
+    fn holds(lhs: StackItem, rhs: StackItem, name: &str) -> bool {
+        match run(vec![lhs, rhs], name).unwrap().as_slice() {
+            [result] if *result == truth(true) => true,
+            [result] if *result == truth(false) => false,
+            other => panic!("{} gave {:?}", name, other),
+        }
+    }
+
+    fn number(value: f64) -> StackItem {
+        StackItem::Number(value)
+    }
+
+    #[test]
+    fn comparisons_give_exact_truth_values() {
+        assert!(holds(number(1.0), number(2.0), "lt"));
+        assert!(!holds(number(2.0), number(2.0), "lt"));
+        assert!(holds(number(2.0), number(2.0), "le"));
+        assert!(holds(number(3.0), number(2.0), "gt"));
+        assert!(holds(number(2.0), number(2.0), "ge"));
+        assert!(holds(number(2.0), number(2.0), "eq"));
+        assert!(holds(number(2.0), number(3.0), "ne"));
+        // exact and float values compare by value
+        let third = StackItem::from_exact(Rational::parse("1/3").unwrap().unwrap());
+        assert!(holds(third.clone(), number(0.34), "lt"));
+        assert!(holds(StackItem::Integer(2u64.into()), number(2.0), "eq"));
+        assert!(holds(StackItem::String("abc".to_string()), StackItem::String("abd".to_string()), "lt"));
+    }
+
+    #[test]
+    fn nan_is_unordered() {
+        for name in ["eq", "lt", "le", "gt", "ge"] {
+            assert!(!holds(number(f64::NAN), number(1.0), name), "NaN {} 1", name);
+        }
+        assert!(holds(number(f64::NAN), number(f64::NAN), "ne"));
+    }
+
+    #[test]
+    fn complex_numbers_only_compare_for_equality() {
+        let i = StackItem::Complex(Complex::new(0.0, 1.0));
+        assert!(holds(i.clone(), i.clone(), "eq"));
+        assert!(run(vec![i.clone(), i], "lt").is_err());
+    }
+
+    #[test]
+    fn arrays_compare_into_masks() {
+        let a = StackItem::Array(vec![vec![1.0, 5.0], vec![3.0, 0.0]]);
+        let b = StackItem::Array(vec![vec![2.0, 5.0], vec![1.0, 0.0]]);
+        assert_eq!(run(vec![a.clone(), b], "lt"), Ok(vec![StackItem::Array(vec![vec![1.0, 0.0], vec![0.0, 0.0]])]));
+        assert_eq!(run(vec![a.clone(), number(2.0)], "ge"), Ok(vec![StackItem::Array(vec![vec![0.0, 1.0], vec![1.0, 0.0]])]));
+        assert!(run(vec![a, StackItem::Array(vec![vec![1.0]])], "eq").is_err());
+    }
+
+    #[test]
+    fn logic_on_masks() {
+        let mask = StackItem::Array(vec![vec![1.0, 0.0, 2.0]]);
+        let other = StackItem::Array(vec![vec![1.0, 1.0, 0.0]]);
+        assert_eq!(run(vec![mask.clone(), other], "and"), Ok(vec![StackItem::Array(vec![vec![1.0, 0.0, 0.0]])]));
+        assert_eq!(run(vec![mask, number(0.0)], "or"), Ok(vec![StackItem::Array(vec![vec![1.0, 0.0, 1.0]])]));
+        assert_eq!(run(vec![number(3.0)], "not"), Ok(vec![truth(false)]));
+    }
+
+    #[test]
+    fn select_and_filter_use_masks() {
+        let mask = StackItem::Array(vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+        let values = StackItem::Array(vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+        assert_eq!(
+            run(vec![mask.clone(), values.clone(), number(0.0)], "select"),
+            Ok(vec![StackItem::Array(vec![vec![1.0, 0.0], vec![0.0, 4.0]])])
+        );
+        assert_eq!(run(vec![number(0.0), values.clone(), number(9.0)], "select"), Ok(vec![number(9.0)]));
+        assert_eq!(run(vec![values, mask], "filter"), Ok(vec![StackItem::Array(vec![vec![1.0, 4.0]])]));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::data::context::{AppContext, AppMode};
+use crate::data::matrix_editor::{MatrixEditor, Source};
+use crate::math::linalg;
+use crate::stack::item::StackItem;
+use crate::stack::registry::{FunctionSpec, ANY};
+use crate::stack::transaction::pop_operands;
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "edit",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Open the array at level n (default 1) in the matrix editor",
+        handler: edit,
+    },
+    FunctionSpec {
+        name: "write",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Write the edited matrix back over the array it was opened from (ctrl-s)",
+        handler: write,
+    },
+    FunctionSpec {
+        name: "push",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Push the edited matrix as a new array (ctrl-p)",
+        handler: push,
+    },
+    FunctionSpec {
+        name: "new",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Open a blank m x n matrix in the editor (.new m n)",
+        handler: new,
+    },
+    FunctionSpec {
+        name: "insrow",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Insert a blank row above the cursor",
+        handler: insrow,
+    },
+    FunctionSpec {
+        name: "delrow",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Delete the row under the cursor",
+        handler: delrow,
+    },
+    FunctionSpec {
+        name: "inscol",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Insert a blank column left of the cursor",
+        handler: inscol,
+    },
+    FunctionSpec {
+        name: "delcol",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Delete the column under the cursor",
+        handler: delcol,
+    },
+    FunctionSpec {
+        name: "resize",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Resize the open matrix to m x n, keeping the top-left values (.resize m n)",
+        handler: resize,
+    },
+    FunctionSpec {
+        name: "fill",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Set every selected cell to v (v from arg or level 1)",
+        handler: fill,
+    },
+    FunctionSpec {
+        name: "copy",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Copy the selected cells",
+        handler: copy,
+    },
+    FunctionSpec {
+        name: "paste",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Paste copied cells with their top-left corner at the cursor",
+        handler: paste,
+    },
+];
+
+fn level_argument(args: &[String]) -> Result<usize, String> {
+    match args.first() {
+        Some(arg) => match arg.parse::<usize>() {
+            Ok(level) if level > 0 => Ok(level),
+            _ => Err(format!("Expected a stack level, got {}", arg)),
+        },
+        None => Ok(1),
+    }
+}
+
+fn open_editor(context: &mut AppContext) -> Result<&mut MatrixEditor, String> {
+    context.matrix_editor.as_mut().ok_or_else(|| "No matrix is open; use .edit".to_string())
+}
+
+fn edit(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let level = level_argument(&args)?;
+    if level > context.stack.len() {
+        return Err(format!("No item at level {}", level));
+    }
+    let index = context.stack.len() - level;
+    let StackItem::Array(arr) = &context.stack[index] else {
+        return Err(format!("Level {} must be array, got {}", level, context.stack[index].kind().name()));
+    };
+    if arr.iter().all(|row| row.is_empty()) {
+        return Err("Cannot edit an empty array; use .new m n".to_string());
+    }
+    context.matrix_editor = Some(MatrixEditor::new(arr.clone(), Some(index)));
+    context.current_mode = AppMode::Matrix;
+    Ok(())
+}
+
+fn write(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let editor = open_editor(context)?;
+    let data = editor.data.clone();
+    let source = editor.source.as_ref().ok_or("The matrix did not come from the stack; use .push")?;
+    let (index, original) = (source.index, StackItem::Array(source.original.clone()));
+    // the stack may have changed since the matrix was opened; only write over
+    // the same array, still unchanged at the same position
+    if context.stack.get(index) != Some(&original) {
+        return Err("The original array is no longer on the stack; use .push".to_string());
+    }
+    context.stack[index] = StackItem::Array(data.clone());
+    open_editor(context)?.source = Some(Source { index, original: data });
+    Ok(())
+}
+
+fn push(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let data = open_editor(context)?.data.clone();
+    context.stack.push(StackItem::Array(data));
+    // later writes go to the copy just pushed
+    let index = context.stack.len() - 1;
+    let editor = open_editor(context)?;
+    editor.source = Some(Source { index, original: editor.data.clone() });
+    Ok(())
+}
+
+fn dimensions_argument(args: &[String]) -> Result<(usize, usize), String> {
+    let dimension = |index: usize| -> Result<usize, String> {
+        let arg = args.get(index).ok_or("Expected dimensions m n")?;
+        let size = arg.parse::<usize>().map_err(|_| format!("Expected a positive size, got {}", arg))?;
+        linalg::check_dimension(size)?;
+        Ok(size)
+    };
+    Ok((dimension(0)?, dimension(1)?))
+}
+
+fn new(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let (rows, cols) = dimensions_argument(&args)?;
+    context.matrix_editor = Some(MatrixEditor::new(vec![vec![0.0; cols]; rows], None));
+    context.current_mode = AppMode::Matrix;
+    Ok(())
+}
+
+fn insrow(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    open_editor(context)?.insert_row();
+    Ok(())
+}
+
+fn delrow(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    open_editor(context)?.delete_row()
+}
+
+fn inscol(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    open_editor(context)?.insert_col();
+    Ok(())
+}
+
+fn delcol(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    open_editor(context)?.delete_col()
+}
+
+fn resize(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let (rows, cols) = dimensions_argument(&args)?;
+    open_editor(context)?.resize(rows, cols);
+    Ok(())
+}
+
+fn fill(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    open_editor(context)?;
+    let value = match args.first() {
+        Some(arg) => arg.parse::<f64>().map_err(|_| format!("Expected a number, got {}", arg))?,
+        None => pop_operands(&mut context.stack, 1)?.pop().unwrap().as_number()?,
+    };
+    open_editor(context)?.fill(value);
+    Ok(())
+}
+
+fn copy(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    open_editor(context)?.copy();
+    Ok(())
+}
+
+fn paste(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    open_editor(context)?.paste()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stack::functions::route_function_call;
+
+    fn call(context: &mut AppContext, name: &str, args: &[&str]) -> Result<(), String> {
+        route_function_call(name.to_string(), args.iter().map(|arg| arg.to_string()).collect(), context)
+    }
+
+    #[test]
+    fn new_and_resize_reject_sizes_out_of_range() {
+        let mut context = AppContext::default();
+        assert!(call(&mut context, "new", &["100000", "100000"]).is_err());
+        assert!(call(&mut context, "new", &["0", "3"]).is_err());
+        assert!(context.matrix_editor.is_none());
+
+        call(&mut context, "new", &["2", "3"]).unwrap();
+        assert!(call(&mut context, "resize", &["2", "1001"]).is_err());
+        call(&mut context, "resize", &["1000", "1"]).unwrap();
+        assert_eq!(context.matrix_editor.as_ref().unwrap().shape(), (1000, 1));
+    }
+}
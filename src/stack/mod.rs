@@ -1,2 +1,18 @@
 pub(crate) mod item;
-pub(crate) mod functions;
\ No newline at end of file
+pub(crate) mod functions;
+pub(crate) mod linalg;
+pub(crate) mod statistics;
+pub(crate) mod complex;
+pub(crate) mod scientific;
+pub(crate) mod binary;
+pub(crate) mod units;
+pub(crate) mod strings;
+pub(crate) mod logic;
+pub(crate) mod display;
+pub(crate) mod constants;
+pub(crate) mod datetime;
+pub(crate) mod variables;
+pub(crate) mod matrix;
+pub(crate) mod registry;
+pub(crate) mod transaction;
+pub(crate) mod history;
\ No newline at end of file
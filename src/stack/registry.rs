@@ -0,0 +1,139 @@
+use crate::data::context::AppContext;
+use crate::stack::{binary, complex, constants, datetime, display, functions, linalg, logic, matrix, scientific, statistics, strings, units, variables};
+use crate::stack::item::{ItemKind, StackItem};
+use crate::stack::transaction::require_depth;
+
+pub(crate) type Handler = fn(Vec<String>, &mut AppContext) -> Result<(), String>;
+
+// Accepted kinds shared by many functions
+pub(crate) const ANY: &[ItemKind] = &[
+    ItemKind::Number, ItemKind::Array, ItemKind::Complex, ItemKind::Integer, ItemKind::Rational, ItemKind::Word,
+    ItemKind::Quantity, ItemKind::String, ItemKind::Date, ItemKind::Duration,
+];
+pub(crate) const REAL: &[ItemKind] = &[ItemKind::Number, ItemKind::Integer, ItemKind::Rational];
+pub(crate) const SCALAR: &[ItemKind] = &[ItemKind::Number, ItemKind::Integer, ItemKind::Rational, ItemKind::Complex];
+pub(crate) const NUMERIC: &[ItemKind] = &[
+    ItemKind::Number, ItemKind::Integer, ItemKind::Rational, ItemKind::Complex, ItemKind::Array,
+];
+
+// Metadata for a built-in function. Dispatch, argument checks, error messages
+// and help listings are all driven from these entries.
+pub(crate) struct FunctionSpec {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    // number of stack items the function consumes or inspects
+    pub arity: usize,
+    // kinds each of those items may have, one entry per level starting at level 1;
+    // empty for functions that take a variable number of items and check them themselves
+    pub accepts: &'static [&'static [ItemKind]],
+    pub description: &'static str,
+    pub handler: Handler,
+}
+
+// Each group of built-ins contributes its own table, so adding functions
+// never means editing a central match
+const TABLES: &[&[FunctionSpec]] = &[
+    functions::FUNCTIONS,
+    linalg::FUNCTIONS,
+    statistics::FUNCTIONS,
+    complex::FUNCTIONS,
+    scientific::FUNCTIONS,
+    binary::FUNCTIONS,
+    units::FUNCTIONS,
+    strings::FUNCTIONS,
+    logic::FUNCTIONS,
+    display::FUNCTIONS,
+    constants::FUNCTIONS,
+    datetime::FUNCTIONS,
+    variables::FUNCTIONS,
+    matrix::FUNCTIONS,
+];
+
+pub(crate) fn builtins() -> impl Iterator<Item = &'static FunctionSpec> {
+    TABLES.iter().flat_map(|table| table.iter())
+}
+
+pub(crate) fn lookup(name: &str) -> Option<&'static FunctionSpec> {
+    builtins().find(|spec| spec.name == name || spec.aliases.contains(&name))
+}
+
+impl FunctionSpec {
+    // Checks the stack holds enough items of the right kinds before anything is popped
+    pub(crate) fn validate(&self, stack: &[StackItem]) -> Result<(), String> {
+        require_depth(stack, self.arity).map_err(|e| format!("{}: {}", self.name, e))?;
+        for (index, kinds) in self.accepts.iter().enumerate() {
+            let level = index + 1;
+            let item = &stack[stack.len() - level];
+            if !kinds.contains(&item.kind()) {
+                return Err(format!(
+                    "{}: level {} must be {}, got {}",
+                    self.name, level, kind_names(kinds), item.kind().name()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // e.g. "mpow 2:array 1:number|integer", deepest level first as the items are entered
+    pub(crate) fn signature(&self) -> String {
+        let mut signature = self.name.to_string();
+        if !self.aliases.is_empty() {
+            signature.push_str(&format!(" ({})", self.aliases.join(", ")));
+        }
+        for (index, kinds) in self.accepts.iter().enumerate().rev() {
+            signature.push_str(&format!(" {}:{}", index + 1, kind_names(kinds)));
+        }
+        signature
+    }
+}
+
+fn kind_names(kinds: &[ItemKind]) -> String {
+    if kinds == ANY {
+        return "any".to_string();
+    }
+    kinds.iter().map(|kind| kind.name()).collect::<Vec<&str>>().join("|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn names_and_aliases_are_unique() {
+        let mut seen = HashSet::new();
+        for spec in builtins() {
+            for name in std::iter::once(&spec.name).chain(spec.aliases) {
+                assert!(seen.insert(*name), "{} is defined twice", name);
+            }
+        }
+    }
+
+    #[test]
+    fn accepted_kinds_cover_each_level_of_the_arity() {
+        for spec in builtins() {
+            assert!(
+                spec.accepts.is_empty() || spec.accepts.len() == spec.arity,
+                "{} declares {} levels for arity {}", spec.name, spec.accepts.len(), spec.arity
+            );
+        }
+    }
+
+    #[test]
+    fn validate_checks_depth_and_each_level() {
+        let mpow = lookup("mpow").unwrap();
+        let matrix = StackItem::Array(vec![vec![1.0]]);
+        let two = StackItem::Number(2.0);
+        assert!(mpow.validate(std::slice::from_ref(&two)).is_err());
+        assert!(mpow.validate(&[two.clone(), matrix.clone()]).unwrap_err().contains("level 1 must be number|integer"));
+        assert!(mpow.validate(&[two.clone(), two.clone()]).unwrap_err().contains("level 2 must be array"));
+        assert!(mpow.validate(&[StackItem::String("x".to_string()), matrix, two]).is_ok());
+    }
+
+    #[test]
+    fn signatures_list_levels_deepest_first() {
+        assert_eq!(lookup("mpow").unwrap().signature(), "mpow 2:array 1:number|integer");
+        assert_eq!(lookup("+").unwrap().signature(), "add (+) 2:any 1:any");
+        assert_eq!(lookup("depth").unwrap().signature(), "depth");
+    }
+}
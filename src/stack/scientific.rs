@@ -0,0 +1,428 @@
+use crate::data::context::AppContext;
+use crate::data::settings::AngleMode;
+use crate::math::bigint::BigInt;
+use crate::math::complex::Complex;
+use crate::math::rational::Rational;
+use crate::stack::item::{ItemKind, StackItem};
+use crate::stack::registry::{FunctionSpec, ANY, NUMERIC, REAL};
+use crate::stack::transaction::pop_operands;
+
+const ROUNDABLE: &[ItemKind] = &[ItemKind::Number, ItemKind::Integer, ItemKind::Rational, ItemKind::Array];
+
+// Real arguments and results of the circular functions follow the angle mode;
+// complex ones are always in radians.
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "sin",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Sine",
+        handler: sin,
+    },
+    FunctionSpec {
+        name: "cos",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Cosine",
+        handler: cos,
+    },
+    FunctionSpec {
+        name: "tan",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Tangent",
+        handler: tan,
+    },
+    FunctionSpec {
+        name: "asin",
+        aliases: &["arcsin"],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Inverse sine",
+        handler: asin,
+    },
+    FunctionSpec {
+        name: "acos",
+        aliases: &["arccos"],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Inverse cosine",
+        handler: acos,
+    },
+    FunctionSpec {
+        name: "atan",
+        aliases: &["arctan"],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Inverse tangent",
+        handler: atan,
+    },
+    FunctionSpec {
+        name: "atan2",
+        aliases: &[],
+        arity: 2,
+        accepts: &[REAL, REAL],
+        description: "Angle of the point (x = level 1, y = level 2)",
+        handler: atan2,
+    },
+    FunctionSpec {
+        name: "sinh",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Hyperbolic sine",
+        handler: sinh,
+    },
+    FunctionSpec {
+        name: "cosh",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Hyperbolic cosine",
+        handler: cosh,
+    },
+    FunctionSpec {
+        name: "tanh",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Hyperbolic tangent",
+        handler: tanh,
+    },
+    FunctionSpec {
+        name: "asinh",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Inverse hyperbolic sine",
+        handler: asinh,
+    },
+    FunctionSpec {
+        name: "acosh",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Inverse hyperbolic cosine",
+        handler: acosh,
+    },
+    FunctionSpec {
+        name: "atanh",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Inverse hyperbolic tangent",
+        handler: atanh,
+    },
+    FunctionSpec {
+        name: "exp",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Natural exponential",
+        handler: exp,
+    },
+    FunctionSpec {
+        name: "log10",
+        aliases: &["log"],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Base 10 logarithm",
+        handler: log10,
+    },
+    FunctionSpec {
+        name: "log2",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Base 2 logarithm",
+        handler: log2,
+    },
+    FunctionSpec {
+        name: "cbrt",
+        aliases: &[],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "Cube root; real for negative numbers",
+        handler: cbrt,
+    },
+    FunctionSpec {
+        name: "floor",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ROUNDABLE],
+        description: "Round down to a whole number",
+        handler: floor,
+    },
+    FunctionSpec {
+        name: "ceil",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ROUNDABLE],
+        description: "Round up to a whole number",
+        handler: ceil,
+    },
+    FunctionSpec {
+        name: "round",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ROUNDABLE],
+        description: "Round to the nearest whole number, halves away from zero",
+        handler: round,
+    },
+    FunctionSpec {
+        name: "sign",
+        aliases: &["sgn"],
+        arity: 1,
+        accepts: &[NUMERIC],
+        description: "-1, 0 or 1 by sign; a complex number divided by its modulus",
+        handler: sign,
+    },
+    FunctionSpec {
+        name: "mod",
+        aliases: &[],
+        arity: 2,
+        accepts: &[ANY, ANY],
+        description: "Level 2 modulo level 1, with the sign of the divisor",
+        handler: modulo,
+    },
+    FunctionSpec {
+        name: "deg",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Angles in degrees",
+        handler: deg,
+    },
+    FunctionSpec {
+        name: "rad",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Angles in radians",
+        handler: rad,
+    },
+    FunctionSpec {
+        name: "grad",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Angles in gradians",
+        handler: grad,
+    },
+];
+
+fn apply<R, C>(context: &mut AppContext, name: &str, real: R, complex: C) -> Result<(), String>
+where
+    R: Fn(f64) -> Option<f64>,
+    C: Fn(Complex) -> Complex,
+{
+    let item = pop_operands(&mut context.stack, 1)?.pop().unwrap();
+    context.stack.push(item.map_math(name, real, complex)?);
+    Ok(())
+}
+
+fn sin(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let mode = context.angle;
+    apply(context, "sin", |x| Some(mode.sin_cos(x).0), Complex::sin)
+}
+
+fn cos(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let mode = context.angle;
+    apply(context, "cos", |x| Some(mode.sin_cos(x).1), Complex::cos)
+}
+
+fn tan(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let mode = context.angle;
+    apply(context, "tan", |x| {
+        let (sin, cos) = mode.sin_cos(x);
+        Some(sin / cos)
+    }, Complex::tan)
+}
+
+fn asin(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let mode = context.angle;
+    apply(context, "asin", |x| (x.abs() <= 1.0).then(|| mode.radians_to_mode(x.asin())), Complex::asin)
+}
+
+fn acos(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let mode = context.angle;
+    apply(context, "acos", |x| (x.abs() <= 1.0).then(|| mode.radians_to_mode(x.acos())), Complex::acos)
+}
+
+fn atan(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let mode = context.angle;
+    apply(context, "atan", |x| Some(mode.radians_to_mode(x.atan())), Complex::atan)
+}
+
+fn atan2(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let operands = pop_operands(&mut context.stack, 2)?;
+    let (y, x) = (operands[0].as_number()?, operands[1].as_number()?);
+    context.stack.push(StackItem::Number(context.angle.radians_to_mode(y.atan2(x))));
+    Ok(())
+}
+
+fn sinh(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply(context, "sinh", |x| Some(x.sinh()), Complex::sinh)
+}
+
+fn cosh(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply(context, "cosh", |x| Some(x.cosh()), Complex::cosh)
+}
+
+fn tanh(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply(context, "tanh", |x| Some(x.tanh()), Complex::tanh)
+}
+
+fn asinh(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply(context, "asinh", |x| Some(x.asinh()), Complex::asinh)
+}
+
+fn acosh(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply(context, "acosh", |x| (x >= 1.0).then(|| x.acosh()), Complex::acosh)
+}
+
+fn atanh(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply(context, "atanh", |x| (x.abs() <= 1.0).then(|| x.atanh()), Complex::atanh)
+}
+
+fn exp(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply(context, "exp", |x| Some(x.exp()), Complex::exp)
+}
+
+fn log10(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply(context, "log10", |x| (x >= 0.0).then(|| x.log10()), |c| c.ln() / Complex::from(std::f64::consts::LN_10))
+}
+
+fn log2(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply(context, "log2", |x| (x >= 0.0).then(|| x.log2()), |c| c.ln() / Complex::from(std::f64::consts::LN_2))
+}
+
+fn cbrt(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    apply(context, "cbrt", |x| Some(x.cbrt()), |c| c.pow(Complex::from(1.0 / 3.0)))
+}
+
+// Exact values round to integers; floats and array elements stay floats
+fn rounding(context: &mut AppContext, exact: fn(&Rational) -> BigInt, real: fn(f64) -> f64) -> Result<(), String> {
+    let item = pop_operands(&mut context.stack, 1)?.pop().unwrap();
+    let result = match item.as_exact() {
+        Some(value) => StackItem::Integer(exact(&value)),
+        None => item.map_math("rounding", |x| Some(real(x)), |c| c)?,
+    };
+    context.stack.push(result);
+    Ok(())
+}
+
+fn floor(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    rounding(context, Rational::floor, f64::floor)
+}
+
+fn ceil(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    rounding(context, Rational::ceil, f64::ceil)
+}
+
+fn round(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    rounding(context, Rational::round, f64::round)
+}
+
+fn sign(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let item = pop_operands(&mut context.stack, 1)?.pop().unwrap();
+    let result = match item.as_exact() {
+        Some(value) => {
+            let sign = match value.numerator() {
+                n if n.is_zero() => 0,
+                n if n.is_negative() => -1,
+                _ => 1,
+            };
+            StackItem::Integer(BigInt::from(sign as i64))
+        },
+        None => item.map_math(
+            "sign",
+            |x| Some(if x == 0.0 { 0.0 } else { x.signum() }),
+            |c| if c.abs() == 0.0 { c } else { c / Complex::from(c.abs()) },
+        )?,
+    };
+    context.stack.push(result);
+    Ok(())
+}
+
+fn modulo(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let mut operands = pop_operands(&mut context.stack, 2)?;
+    let rhs = operands.pop().unwrap();
+    let lhs = operands.pop().unwrap();
+    context.stack.push(lhs.modulo(rhs)?);
+    Ok(())
+}
+
+fn set_angle_mode(context: &mut AppContext, mode: AngleMode) -> Result<(), String> {
+    context.angle = mode;
+    Ok(())
+}
+
+fn deg(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    set_angle_mode(context, AngleMode::Deg)
+}
+
+fn rad(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    set_angle_mode(context, AngleMode::Rad)
+}
+
+fn grad(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    set_angle_mode(context, AngleMode::Grad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stack::functions::route_function_call;
+
+    fn run(angle: AngleMode, name: &str, x: f64) -> f64 {
+        let mut context = AppContext { angle, stack: vec![StackItem::Number(x)], ..Default::default() };
+        route_function_call(name.to_string(), Vec::new(), &mut context).unwrap();
+        match context.stack.as_slice() {
+            [StackItem::Number(result)] => *result,
+            other => panic!("{} {} gave {:?}", name, x, other),
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn right_angles_are_exact_in_degrees_and_grads() {
+        for (mode, quarter) in [(AngleMode::Deg, 90.0), (AngleMode::Grad, 100.0)] {
+            for turns in -3i32..=5 {
+                let angle = turns as f64 * quarter;
+                let (sin, cos) = [(0.0, 1.0), (1.0, 0.0), (0.0, -1.0), (-1.0, 0.0)][turns.rem_euclid(4) as usize];
+                assert_eq!(run(mode, "sin", angle), sin, "sin {} in {}", angle, mode.label());
+                assert_eq!(run(mode, "cos", angle), cos, "cos {} in {}", angle, mode.label());
+            }
+            assert_eq!(run(mode, "tan", 2.0 * quarter), 0.0);
+        }
+        // sin 180 is +0, not -0, so it displays as 0
+        assert!(run(AngleMode::Deg, "sin", 180.0).is_sign_positive());
+    }
+
+    #[test]
+    fn other_angles_follow_the_mode() {
+        assert!(close(run(AngleMode::Deg, "sin", 30.0), 0.5));
+        assert!(close(run(AngleMode::Deg, "cos", -60.0), 0.5));
+        assert!(close(run(AngleMode::Deg, "tan", 45.0), 1.0));
+        assert!(close(run(AngleMode::Deg, "sin", 390.0), 0.5));
+        assert!(close(run(AngleMode::Grad, "sin", 50.0), 0.5f64.sqrt()));
+        assert!(close(run(AngleMode::Rad, "cos", std::f64::consts::PI / 3.0), 0.5));
+        // radians have no exact multiples of pi, so the rounding error shows
+        assert!(run(AngleMode::Rad, "sin", std::f64::consts::PI).abs() < 1e-15);
+    }
+
+    #[test]
+    fn inverses_answer_in_the_mode() {
+        assert_eq!(run(AngleMode::Deg, "asin", 1.0), 90.0);
+        assert!(close(run(AngleMode::Deg, "acos", 0.5), 60.0));
+        assert!(close(run(AngleMode::Grad, "atan", 1.0), 50.0));
+        assert!(close(run(AngleMode::Rad, "atan", 1.0), std::f64::consts::FRAC_PI_4));
+    }
+}
@@ -0,0 +1,284 @@
+use crate::data::context::AppContext;
+use crate::math::linalg::{shape, transpose};
+use crate::math::stats;
+use crate::stack::item::{ItemKind, StackItem};
+use crate::stack::registry::FunctionSpec;
+use crate::stack::transaction::pop_operands;
+
+const ARRAY: &[ItemKind] = &[ItemKind::Array];
+
+// Reductions work on a whole vector, or per column of a matrix unless an
+// argument asks otherwise: ".mean rows", ".mean cols" or ".mean all".
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "mean",
+        aliases: &["avg"],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Arithmetic mean (arg: rows, cols or all)",
+        handler: mean,
+    },
+    FunctionSpec {
+        name: "median",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Median (arg: rows, cols or all)",
+        handler: median,
+    },
+    FunctionSpec {
+        name: "mode",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Most frequent value, smallest on ties (arg: rows, cols or all)",
+        handler: mode,
+    },
+    FunctionSpec {
+        name: "var",
+        aliases: &["variance"],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Sample variance (arg: rows, cols or all)",
+        handler: var,
+    },
+    FunctionSpec {
+        name: "pvar",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Population variance (arg: rows, cols or all)",
+        handler: pvar,
+    },
+    FunctionSpec {
+        name: "sdev",
+        aliases: &["stddev"],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Sample standard deviation (arg: rows, cols or all)",
+        handler: sdev,
+    },
+    FunctionSpec {
+        name: "psdev",
+        aliases: &["pstd"],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Population standard deviation (arg: rows, cols or all)",
+        handler: psdev,
+    },
+    FunctionSpec {
+        name: "min",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Smallest value (arg: rows, cols or all)",
+        handler: min,
+    },
+    FunctionSpec {
+        name: "max",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Largest value (arg: rows, cols or all)",
+        handler: max,
+    },
+    FunctionSpec {
+        name: "pctl",
+        aliases: &["percentile"],
+        arity: 0,
+        accepts: &[],
+        description: "p-th percentile, p from 0 to 100 (p from arg or level 1; then rows, cols or all)",
+        handler: pctl,
+    },
+    FunctionSpec {
+        name: "cov",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Sample covariance matrix of the columns",
+        handler: cov,
+    },
+    FunctionSpec {
+        name: "corr",
+        aliases: &[],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Correlation matrix of the columns",
+        handler: corr,
+    },
+    FunctionSpec {
+        name: "linreg",
+        aliases: &["lr"],
+        arity: 1,
+        accepts: &[ARRAY],
+        description: "Fit y = a x + b to x (level 2) and y (level 1), or an n x 2 [x y] array; pushes a, b and r²",
+        handler: linreg,
+    },
+];
+
+enum Axis {
+    All,
+    Rows,
+    Cols,
+}
+
+// Vectors are reduced whole by default, matrices column by column
+fn axis_argument(args: &[String], data: &[Vec<f64>]) -> Result<Axis, String> {
+    let (rows, cols) = shape(data);
+    match args.iter().map(|arg| arg.as_str()).find(|arg| arg.parse::<f64>().is_err()) {
+        Some("all") => Ok(Axis::All),
+        Some("rows" | "row") => Ok(Axis::Rows),
+        Some("cols" | "col" | "columns") => Ok(Axis::Cols),
+        Some(other) => Err(format!("Expected rows, cols or all, got {}", other)),
+        None if rows == 1 || cols == 1 => Ok(Axis::All),
+        None => Ok(Axis::Cols),
+    }
+}
+
+// Per-row results come back as a column, per-column results as a row
+fn reduce_array<F>(data: &[Vec<f64>], axis: Axis, f: F) -> Result<StackItem, String>
+where
+    F: Fn(&[f64]) -> Result<f64, String>,
+{
+    match axis {
+        Axis::All => Ok(StackItem::Number(f(&data.concat())?)),
+        Axis::Rows => {
+            let results = data.iter().map(|row| f(row).map(|x| vec![x])).collect::<Result<_, String>>()?;
+            Ok(StackItem::Array(results))
+        },
+        Axis::Cols => {
+            let results = transpose(data).iter().map(|col| f(col)).collect::<Result<_, String>>()?;
+            Ok(StackItem::Array(vec![results]))
+        },
+    }
+}
+
+fn reduce<F>(args: Vec<String>, context: &mut AppContext, f: F) -> Result<(), String>
+where
+    F: Fn(&[f64]) -> Result<f64, String>,
+{
+    let data = pop_operands(&mut context.stack, 1)?.pop().unwrap().into_array()?;
+    let axis = axis_argument(&args, &data)?;
+    context.stack.push(reduce_array(&data, axis, f)?);
+    Ok(())
+}
+
+fn mean(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    reduce(args, context, stats::mean)
+}
+
+fn median(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    reduce(args, context, stats::median)
+}
+
+fn mode(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    reduce(args, context, stats::mode)
+}
+
+fn var(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    reduce(args, context, |values| stats::variance(values, true))
+}
+
+fn pvar(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    reduce(args, context, |values| stats::variance(values, false))
+}
+
+fn sdev(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    reduce(args, context, |values| stats::std_dev(values, true))
+}
+
+fn psdev(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    reduce(args, context, |values| stats::std_dev(values, false))
+}
+
+fn min(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    reduce(args, context, stats::min)
+}
+
+fn max(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    reduce(args, context, stats::max)
+}
+
+fn pctl(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let p = match args.iter().find_map(|arg| arg.parse::<f64>().ok()) {
+        Some(p) => p,
+        None => pop_operands(&mut context.stack, 1)?.pop().unwrap().as_number()?,
+    };
+    reduce(args, context, |values| stats::percentile(values, p))
+}
+
+fn cov(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let data = pop_operands(&mut context.stack, 1)?.pop().unwrap().into_array()?;
+    context.stack.push(StackItem::Array(stats::covariance(&data)?));
+    Ok(())
+}
+
+fn corr(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let data = pop_operands(&mut context.stack, 1)?.pop().unwrap().into_array()?;
+    context.stack.push(StackItem::Array(stats::correlation(&data)?));
+    Ok(())
+}
+
+fn linreg(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let top = pop_operands(&mut context.stack, 1)?.pop().unwrap().into_array()?;
+    let (rows, cols) = shape(&top);
+    // an n x 2 array can't be a vector, so it holds x and y columns side by side
+    let (x, y) = if rows >= 2 && cols == 2 {
+        let columns = transpose(&top);
+        (columns[0].clone(), columns[1].clone())
+    } else {
+        let x = pop_operands(&mut context.stack, 1)?.pop().unwrap().into_array()?;
+        (x.concat(), top.concat())
+    };
+    let fit = stats::linear_regression(&x, &y)?;
+    context.stack.extend([
+        StackItem::Number(fit.slope),
+        StackItem::Number(fit.intercept),
+        StackItem::Number(fit.r_squared),
+    ]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stack::functions::route_function_call;
+
+    fn run(stack: Vec<StackItem>, name: &str, args: &[&str]) -> Result<Vec<StackItem>, String> {
+        let mut context = AppContext { stack, ..Default::default() };
+        route_function_call(name.to_string(), args.iter().map(|arg| arg.to_string()).collect(), &mut context)?;
+        Ok(context.stack)
+    }
+
+    fn table() -> StackItem {
+        StackItem::Array(vec![vec![1.0, 10.0], vec![2.0, 20.0], vec![6.0, 30.0]])
+    }
+
+    #[test]
+    fn reductions_follow_the_axis() {
+        assert_eq!(run(vec![table()], "mean", &[]), Ok(vec![StackItem::Array(vec![vec![3.0, 20.0]])]));
+        assert_eq!(run(vec![table()], "mean", &["rows"]), Ok(vec![StackItem::Array(vec![vec![5.5], vec![11.0], vec![18.0]])]));
+        assert_eq!(run(vec![table()], "max", &["all"]), Ok(vec![StackItem::Number(30.0)]));
+        assert_eq!(run(vec![StackItem::Array(vec![vec![1.0], vec![3.0]])], "mean", &[]), Ok(vec![StackItem::Number(2.0)]));
+        assert_eq!(run(vec![table()], "sdev", &[]), Ok(vec![StackItem::Array(vec![vec![7f64.sqrt(), 10.0]])]));
+        assert!(run(vec![table()], "mean", &["diagonal"]).is_err());
+    }
+
+    #[test]
+    fn linreg_takes_columns_or_two_vectors() {
+        let expected = Ok(vec![StackItem::Number(2.0), StackItem::Number(1.0), StackItem::Number(1.0)]);
+        let columns = StackItem::Array(vec![vec![1.0, 3.0], vec![2.0, 5.0], vec![3.0, 7.0]]);
+        assert_eq!(run(vec![columns], "linreg", &[]), expected);
+        let x = StackItem::Array(vec![vec![1.0, 2.0, 3.0]]);
+        let y = StackItem::Array(vec![vec![3.0, 5.0, 7.0]]);
+        assert_eq!(run(vec![x, y], "linreg", &[]), expected);
+    }
+
+    #[test]
+    fn corr_of_a_constant_column_fails_without_losing_it() {
+        let data = StackItem::Array(vec![vec![1.0, 4.0], vec![2.0, 4.0]]);
+        let mut context = AppContext { stack: vec![data.clone()], ..Default::default() };
+        assert!(route_function_call("corr".to_string(), Vec::new(), &mut context).is_err());
+        assert_eq!(context.stack, vec![data]);
+    }
+}
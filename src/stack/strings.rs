@@ -0,0 +1,259 @@
+use crate::control::flow::value_to_item;
+use crate::control::parsing::{InputType, ParsedInput};
+use crate::data::context::AppContext;
+use crate::stack::functions::count_argument;
+use crate::stack::item::{ItemKind, StackItem};
+use crate::stack::registry::{FunctionSpec, ANY};
+use crate::stack::transaction::pop_operands;
+use crate::utils::json::Json;
+
+const STRING: &[ItemKind] = &[ItemKind::String];
+const FORMATTABLE: &[ItemKind] = &[ItemKind::Number, ItemKind::Integer, ItemKind::Rational, ItemKind::Quantity];
+// The same limit the fix, sci and eng display modes put on their digit count
+const MAX_PLACES: usize = 17;
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "concat",
+        aliases: &[],
+        arity: 2,
+        accepts: &[STRING, STRING],
+        description: "Join level 2 and level 1 strings",
+        handler: concat,
+    },
+    FunctionSpec {
+        name: "len",
+        aliases: &["length"],
+        arity: 1,
+        accepts: &[STRING],
+        description: "Number of characters in a string",
+        handler: len,
+    },
+    FunctionSpec {
+        name: "substr",
+        aliases: &["sub"],
+        arity: 0,
+        accepts: &[],
+        description: "Substring from a 1-based start with a length (args or levels 2 and 1)",
+        handler: substr,
+    },
+    FunctionSpec {
+        name: "find",
+        aliases: &["pos"],
+        arity: 0,
+        accepts: &[],
+        description: "1-based position of the level 1 (or argument) string in level 2, 0 if absent",
+        handler: find,
+    },
+    FunctionSpec {
+        name: "str",
+        aliases: &["->str"],
+        arity: 1,
+        accepts: &[ANY],
+        description: "Convert an item to its text",
+        handler: to_str,
+    },
+    FunctionSpec {
+        name: "num",
+        aliases: &["str->"],
+        arity: 1,
+        accepts: &[STRING],
+        description: "Parse a string as a value",
+        handler: to_num,
+    },
+    FunctionSpec {
+        name: "tojson",
+        aliases: &["->json"],
+        arity: 1,
+        accepts: &[ANY],
+        description: "Convert an item to a JSON string that fromjson reads back exactly",
+        handler: to_json,
+    },
+    FunctionSpec {
+        name: "fromjson",
+        aliases: &["json->"],
+        arity: 1,
+        accepts: &[STRING],
+        description: "Read an item back from its JSON string",
+        handler: from_json,
+    },
+    FunctionSpec {
+        name: "format",
+        aliases: &["fmt"],
+        arity: 0,
+        accepts: &[],
+        description: "Format a number with n decimal places (n from arg or level 1)",
+        handler: format,
+    },
+];
+
+fn pop_string(context: &mut AppContext) -> Result<String, String> {
+    match pop_operands(&mut context.stack, 1)?.pop().unwrap() {
+        StackItem::String(text) => Ok(text),
+        other => Err(format!("Expected a string, got {}", other.kind().name())),
+    }
+}
+
+// Text of an item without quotes and at full precision, in input syntax where there is one
+fn plain_text(item: &StackItem, context: &AppContext) -> String {
+    match item {
+        StackItem::Number(num) => num.to_string(),
+        StackItem::Array(arr) => arr.iter()
+            .map(|row| row.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" "))
+            .collect::<Vec<String>>()
+            .join("; "),
+        StackItem::Complex(c) => format!("({},{})", c.re, c.im),
+        StackItem::Integer(int) => int.to_string(),
+        StackItem::Rational(rational) => rational.to_string(),
+        StackItem::Word(word) => word.format(context.display.base),
+        StackItem::Quantity(quantity) => format!("{}_{}", quantity.value, quantity.unit),
+        StackItem::String(text) => text.clone(),
+        StackItem::Date(date) => date.to_string(),
+        StackItem::Duration(duration) => duration.to_string(),
+    }
+}
+
+fn concat(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let tail = pop_string(context)?;
+    let head = pop_string(context)?;
+    context.stack.push(StackItem::String(head + &tail));
+    Ok(())
+}
+
+fn len(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let text = pop_string(context)?;
+    context.stack.push(StackItem::Integer((text.chars().count() as u64).into()));
+    Ok(())
+}
+
+fn substr(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let length = count_argument(args.get(1..).unwrap_or(&[]), context)?;
+    let start = count_argument(args.get(..1).unwrap_or(&[]), context)?;
+    if start == 0 {
+        return Err("Positions start at 1".to_string());
+    }
+    let text = pop_string(context)?;
+    let result = text.chars().skip(start - 1).take(length).collect();
+    context.stack.push(StackItem::String(result));
+    Ok(())
+}
+
+fn find(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    // like the other string words, a single argument; text with spaces comes from the stack
+    let needle = match args.as_slice() {
+        [] => pop_string(context)?,
+        [needle] => needle.clone(),
+        _ => return Err("find takes one argument; push text containing spaces as a string".to_string()),
+    };
+    let text = pop_string(context)?;
+    // positions count characters, not bytes
+    let position = text.find(&needle).map_or(0, |byte| text[..byte].chars().count() + 1);
+    context.stack.push(StackItem::Integer((position as u64).into()));
+    Ok(())
+}
+
+fn to_str(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let item = pop_operands(&mut context.stack, 1)?.pop().unwrap();
+    let text = plain_text(&item, context);
+    context.stack.push(StackItem::String(text));
+    Ok(())
+}
+
+fn to_num(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let text = pop_string(context)?;
+    match ParsedInput::create_from_buf(text.clone())?.input_type {
+        InputType::Value(value_type) => {
+            let item = value_to_item(value_type, &context.word);
+            context.stack.push(item);
+            Ok(())
+        },
+        InputType::FunctionCall { .. } | InputType::Identifier(_) => Err(format!("Not a value: {}", text)),
+    }
+}
+
+fn to_json(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let item = pop_operands(&mut context.stack, 1)?.pop().unwrap();
+    context.stack.push(StackItem::String(item.to_json().to_string()));
+    Ok(())
+}
+
+fn from_json(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let text = pop_string(context)?;
+    let item = StackItem::from_json(&Json::parse(&text)?)?;
+    context.stack.push(item);
+    Ok(())
+}
+
+fn format(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let places = count_argument(&args, context)?;
+    if places > MAX_PLACES {
+        return Err(format!("At most {} decimal places, got {}", MAX_PLACES, places));
+    }
+    let item = pop_operands(&mut context.stack, 1)?.pop().unwrap();
+    let text = match &item {
+        StackItem::Quantity(quantity) => format!("{:.*}_{}", places, quantity.value, quantity.unit),
+        other => format!("{:.*}", places, other.as_number()?),
+    };
+    context.stack.push(StackItem::String(text));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stack::functions::route_function_call;
+
+    fn text(value: &str) -> StackItem {
+        StackItem::String(value.to_string())
+    }
+
+    fn run(stack: Vec<StackItem>, name: &str, args: &[&str]) -> Result<Vec<StackItem>, String> {
+        let mut context = AppContext { stack, ..Default::default() };
+        route_function_call(name.to_string(), args.iter().map(|arg| arg.to_string()).collect(), &mut context)?;
+        Ok(context.stack)
+    }
+
+    #[test]
+    fn concat_and_add_join_strings() {
+        assert_eq!(run(vec![text("ab"), text("cé")], "concat", &[]), Ok(vec![text("abcé")]));
+        assert_eq!(text("ab") + text(""), Ok(text("ab")));
+        assert!(run(vec![text("ab"), StackItem::Number(1.0)], "concat", &[]).is_err());
+    }
+
+    #[test]
+    fn len_counts_characters() {
+        assert_eq!(run(vec![text("héllo 😀")], "len", &[]), Ok(vec![StackItem::Integer(7u64.into())]));
+        assert_eq!(run(vec![text("")], "len", &[]), Ok(vec![StackItem::Integer(0u64.into())]));
+    }
+
+    #[test]
+    fn substr_takes_start_and_length() {
+        assert_eq!(run(vec![text("héllo")], "substr", &["2", "3"]), Ok(vec![text("éll")]));
+        // from the stack: text start length
+        let stack = vec![text("héllo"), StackItem::Integer(4u64.into()), StackItem::Integer(10u64.into())];
+        assert_eq!(run(stack, "substr", &[]), Ok(vec![text("lo")]));
+        assert_eq!(run(vec![text("abc")], "substr", &["9", "2"]), Ok(vec![text("")]));
+        assert!(run(vec![text("abc")], "substr", &["0", "2"]).is_err());
+    }
+
+    #[test]
+    fn find_gives_a_character_position_or_zero() {
+        assert_eq!(run(vec![text("héllo")], "find", &["l"]), Ok(vec![StackItem::Integer(3u64.into())]));
+        assert_eq!(run(vec![text("a b c"), text("b c")], "find", &[]), Ok(vec![StackItem::Integer(3u64.into())]));
+        assert_eq!(run(vec![text("abc")], "find", &["x"]), Ok(vec![StackItem::Integer(0u64.into())]));
+        assert!(run(vec![text("a b")], "find", &["a", "b"]).is_err());
+    }
+
+    #[test]
+    fn str_and_num_convert() {
+        assert_eq!(run(vec![StackItem::Number(2.5)], "str", &[]), Ok(vec![text("2.5")]));
+        assert_eq!(run(vec![text("-3/7")], "num", &[]).map(|stack| stack[0].to_literal()), Ok("-3/7".to_string()));
+        assert!(run(vec![text(".drop")], "num", &[]).is_err());
+    }
+
+    #[test]
+    fn format_rounds_to_places() {
+        assert_eq!(run(vec![StackItem::Number(2.0 / 3.0)], "format", &["3"]), Ok(vec![text("0.667")]));
+        assert!(run(vec![StackItem::Number(1.0)], "format", &["18"]).is_err());
+    }
+}
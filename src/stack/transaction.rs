@@ -0,0 +1,93 @@
+use crate::data::context::AppContext;
+use crate::stack::item::StackItem;
+
+// Runs an operation against the stack as a unit. If the operation fails the
+// stack and modes are restored to exactly what they were before, so operands are
+// never lost. If it succeeds and changed either, the previous state is kept in the
+// undo history.
+pub(crate) fn run_transaction<F>(context: &mut AppContext, op: F) -> Result<(), String>
+where
+    F: FnOnce(&mut AppContext) -> Result<(), String>,
+{
+    let snapshot = context.snapshot();
+    let result = op(context);
+    match result {
+        Ok(()) => {
+            if !context.matches(&snapshot) {
+                context.history.record(snapshot);
+            }
+        },
+        Err(_) => context.restore(snapshot),
+    }
+    result
+}
+
+// Fails unless the stack holds at least `count` items
+pub(crate) fn require_depth(stack: &[StackItem], count: usize) -> Result<(), String> {
+    if stack.len() < count {
+        Err(format!("Too few arguments: need {}, stack has {}", count, stack.len()))
+    } else {
+        Ok(())
+    }
+}
+
+// Pops `count` items after checking they exist, returned in stack order
+// (deepest first, so a binary operation receives [lhs, rhs])
+pub(crate) fn pop_operands(stack: &mut Vec<StackItem>, count: usize) -> Result<Vec<StackItem>, String> {
+    require_depth(stack, count)?;
+    Ok(stack.split_off(stack.len() - count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::settings::AngleMode;
+
+    #[test]
+    fn failure_restores_everything_it_touched() {
+        let mut context = AppContext { stack: vec![StackItem::Number(1.0), StackItem::Number(2.0)], ..Default::default() };
+        let before = context.snapshot();
+        let result = run_transaction(&mut context, |context| {
+            pop_operands(&mut context.stack, 2)?;
+            context.variables.insert("x".to_string(), StackItem::Number(3.0));
+            context.angle = AngleMode::Grad;
+            context.word.size = 8;
+            Err("failed halfway".to_string())
+        });
+        assert_eq!(result, Err("failed halfway".to_string()));
+        assert!(context.matches(&before));
+        // nothing happened, so there is nothing to undo
+        let mut state = context.snapshot();
+        assert!(!context.history.undo(&mut state));
+    }
+
+    #[test]
+    fn success_is_recorded_only_when_something_changed() {
+        let mut context = AppContext::default();
+        run_transaction(&mut context, |_| Ok(())).unwrap();
+        run_transaction(&mut context, |context| {
+            context.stack.push(StackItem::Number(1.0));
+            context.stack.pop();
+            Ok(())
+        }).unwrap();
+        let mut state = context.snapshot();
+        assert!(!context.history.undo(&mut state));
+
+        run_transaction(&mut context, |context| {
+            context.stack.push(StackItem::Number(1.0));
+            Ok(())
+        }).unwrap();
+        context.undo();
+        assert!(context.stack.is_empty());
+    }
+
+    #[test]
+    fn pop_operands_checks_depth_first() {
+        let mut stack = vec![StackItem::Number(1.0)];
+        assert!(pop_operands(&mut stack, 2).is_err());
+        assert_eq!(stack.len(), 1);
+        stack.push(StackItem::Number(2.0));
+        assert_eq!(pop_operands(&mut stack, 2), Ok(vec![StackItem::Number(1.0), StackItem::Number(2.0)]));
+        assert!(stack.is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::data::context::AppContext;
+use crate::math::units::{Quantity, Unit};
+use crate::stack::item::{ItemKind, StackItem};
+use crate::stack::registry::{FunctionSpec, REAL};
+use crate::stack::transaction::pop_operands;
+
+const QUANTITY: &[ItemKind] = &[ItemKind::Quantity];
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "convert",
+        aliases: &["conv"],
+        arity: 1,
+        accepts: &[QUANTITY],
+        description: "Convert to the unit given as argument, or to the unit of level 1 (.convert km/h)",
+        handler: convert,
+    },
+    FunctionSpec {
+        name: "si",
+        aliases: &["ubase"],
+        arity: 1,
+        accepts: &[QUANTITY],
+        description: "Express a quantity in SI base units",
+        handler: si,
+    },
+    FunctionSpec {
+        name: "unit",
+        aliases: &[],
+        arity: 1,
+        accepts: &[REAL],
+        description: "Attach the unit given as argument to a number (.unit m/s)",
+        handler: attach_unit,
+    },
+    FunctionSpec {
+        name: "uval",
+        aliases: &[],
+        arity: 1,
+        accepts: &[QUANTITY],
+        description: "Strip the unit from a quantity, leaving its value",
+        handler: uval,
+    },
+];
+
+fn unit_argument(args: &[String]) -> Option<Result<Unit, String>> {
+    // "km / h" arrives split on whitespace
+    (!args.is_empty()).then(|| Unit::parse(&args.concat()))
+}
+
+fn pop_quantity(context: &mut AppContext) -> Result<Quantity, String> {
+    match pop_operands(&mut context.stack, 1)?.pop().unwrap() {
+        StackItem::Quantity(quantity) => Ok(quantity),
+        other => Err(format!("Expected a quantity, got {}", other.kind().name())),
+    }
+}
+
+fn convert(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let target = match unit_argument(&args) {
+        Some(unit) => unit?,
+        // without an argument level 1 only supplies the target unit
+        None => pop_quantity(context)?.unit,
+    };
+    let quantity = pop_quantity(context)?;
+    context.stack.push(StackItem::Quantity(quantity.convert(&target)?));
+    Ok(())
+}
+
+fn si(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let quantity = pop_quantity(context)?.to_si_base();
+    context.stack.push(StackItem::Quantity(quantity));
+    Ok(())
+}
+
+fn attach_unit(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let unit = unit_argument(&args).ok_or("Missing unit argument")??;
+    let value = pop_operands(&mut context.stack, 1)?.pop().unwrap().as_number()?;
+    context.stack.push(StackItem::Quantity(Quantity::new(value, unit)));
+    Ok(())
+}
+
+fn uval(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let quantity = pop_quantity(context)?;
+    context.stack.push(StackItem::Number(quantity.value));
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use crate::control::parsing::is_identifier;
+use crate::data::context::{AppContext, AppMode};
+use crate::math::constants;
+use crate::stack::constants::constant_item;
+use crate::stack::functions::route_function_call;
+use crate::stack::item::StackItem;
+use crate::stack::registry::{FunctionSpec, ANY};
+use crate::stack::transaction::{pop_operands, run_transaction};
+
+pub(crate) const FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec {
+        name: "sto",
+        aliases: &["store"],
+        arity: 1,
+        accepts: &[ANY],
+        description: "Pop level 1 into the named variable (.sto x)",
+        handler: sto,
+    },
+    FunctionSpec {
+        name: "rcl",
+        aliases: &["recall"],
+        arity: 0,
+        accepts: &[],
+        description: "Push a copy of the named variable (.rcl x); typing the bare name does the same",
+        handler: rcl,
+    },
+    FunctionSpec {
+        name: "rename",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Rename a variable (.rename old new)",
+        handler: rename,
+    },
+    FunctionSpec {
+        name: "vars",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Switch to the variables view",
+        handler: vars,
+    },
+    FunctionSpec {
+        name: "purge",
+        aliases: &[],
+        arity: 0,
+        accepts: &[],
+        description: "Delete the named variable (.purge x)",
+        handler: purge,
+    },
+];
+
+fn name_argument(args: &[String]) -> Result<&str, String> {
+    name_at(args, 0)
+}
+
+fn name_at(args: &[String], index: usize) -> Result<&str, String> {
+    let name = args.get(index).ok_or("Missing variable name")?;
+    if !is_identifier(name) {
+        return Err(format!("Invalid variable name: {}", name));
+    }
+    Ok(name)
+}
+
+// The value of a bare name: the variable of that name, or else the constant
+pub(crate) fn name_value(name: &str, context: &AppContext) -> Result<StackItem, String> {
+    match (context.variables.get(name), constants::lookup(name)) {
+        (Some(item), _) => Ok(item.clone()),
+        (None, Some(constant)) => Ok(constant_item(constant)),
+        (None, None) => Err(format!("Unknown name: {}", name)),
+    }
+}
+
+pub(crate) fn evaluate_name(name: &str, context: &mut AppContext) -> Result<(), String> {
+    let item = name_value(name, context)?;
+    run_transaction(context, |context| {
+        context.stack.push(item);
+        Ok(())
+    })
+}
+
+fn sto(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let name = name_argument(&args)?;
+    let item = pop_operands(&mut context.stack, 1)?.pop().unwrap();
+    context.variables.insert(name.to_string(), item);
+    Ok(())
+}
+
+fn rcl(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let name = name_argument(&args)?;
+    let item = context.variables.get(name).ok_or_else(|| format!("Unknown variable: {}", name))?;
+    context.stack.push(item.clone());
+    Ok(())
+}
+
+fn purge(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let name = name_argument(&args)?;
+    context.variables.remove(name).ok_or_else(|| format!("Unknown variable: {}", name))?;
+    context.clamp_variable_selection();
+    Ok(())
+}
+
+fn rename(args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    let (old, new) = (name_at(&args, 0)?, name_at(&args, 1)?);
+    if context.variables.contains_key(new) {
+        return Err(format!("Variable {} already exists", new));
+    }
+    let item = context.variables.remove(old).ok_or_else(|| format!("Unknown variable: {}", old))?;
+    context.variables.insert(new.to_string(), item);
+    // follow the variable to its new place in the sorted list
+    context.selected_variable = context.variables.keys().position(|name| name == new).unwrap_or(0);
+    Ok(())
+}
+
+fn vars(_args: Vec<String>, context: &mut AppContext) -> Result<(), String> {
+    context.current_mode = AppMode::Variables;
+    context.clamp_variable_selection();
+    Ok(())
+}
+
+pub(crate) fn push_selected_variable(context: &mut AppContext) -> Result<(), String> {
+    let name = context.selected_variable_name().ok_or("No variables stored")?;
+    evaluate_name(&name, context)
+}
+
+// Stores level 1 under the typed name, or over the selected variable if nothing is typed
+pub(crate) fn store_selected_variable(typed: &str, context: &mut AppContext) -> Result<(), String> {
+    let name = match typed.trim() {
+        "" => context.selected_variable_name().ok_or("Type a name to store level 1 under")?,
+        typed => typed.to_string(),
+    };
+    route_function_call("sto".to_string(), vec![name.clone()], context)?;
+    context.selected_variable = context.variables.keys().position(|key| *key == name).unwrap_or(0);
+    Ok(())
+}
+
+pub(crate) fn rename_selected_variable(typed: &str, context: &mut AppContext) -> Result<(), String> {
+    let old = context.selected_variable_name().ok_or("No variables stored")?;
+    if typed.trim().is_empty() {
+        return Err("Type the new name first".to_string());
+    }
+    route_function_call("rename".to_string(), vec![old, typed.trim().to_string()], context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(context: &mut AppContext, name: &str, args: &[&str]) {
+        route_function_call(name.to_string(), args.iter().map(|arg| arg.to_string()).collect(), context).unwrap();
+    }
+
+    #[test]
+    fn undo_reverts_variable_changes() {
+        let mut context = AppContext::default();
+        context.stack.push(StackItem::Number(5.0));
+
+        call(&mut context, "sto", &["x"]);
+        context.undo();
+        assert!(context.variables.is_empty());
+        assert_eq!(context.stack, vec![StackItem::Number(5.0)]);
+        context.redo();
+        assert_eq!(context.variables.get("x"), Some(&StackItem::Number(5.0)));
+        assert!(context.stack.is_empty());
+
+        call(&mut context, "rename", &["x", "y"]);
+        assert!(context.variables.contains_key("y"));
+        context.undo();
+        assert_eq!(context.variables.keys().collect::<Vec<_>>(), vec!["x"]);
+
+        call(&mut context, "purge", &["x"]);
+        assert!(context.variables.is_empty());
+        context.undo();
+        assert_eq!(context.variables.get("x"), Some(&StackItem::Number(5.0)));
+    }
+
+    #[test]
+    fn failed_rename_leaves_variables_alone() {
+        let mut context = AppContext::default();
+        context.variables.insert("x".to_string(), StackItem::Number(1.0));
+        context.variables.insert("y".to_string(), StackItem::Number(2.0));
+        let before = context.variables.clone();
+        assert!(route_function_call("rename".to_string(), vec!["x".to_string(), "y".to_string()], &mut context).is_err());
+        assert_eq!(context.variables, before);
+        assert!(route_function_call("rename".to_string(), vec!["z".to_string(), "w".to_string()], &mut context).is_err());
+        assert_eq!(context.variables, before);
+    }
+
+    #[test]
+    fn view_keys_store_and_rename_undoably() {
+        let mut context = AppContext::default();
+        context.stack.push(StackItem::Number(1.0));
+        context.stack.push(StackItem::Number(2.0));
+        store_selected_variable("b", &mut context).unwrap();
+        store_selected_variable("a", &mut context).unwrap();
+        assert_eq!(context.selected_variable_name().as_deref(), Some("a"));
+
+        // overwriting the selection needs a value to store
+        assert!(store_selected_variable("", &mut context).is_err());
+        context.stack.push(StackItem::Number(3.0));
+        store_selected_variable("", &mut context).unwrap();
+        assert_eq!(context.variables.get("a"), Some(&StackItem::Number(3.0)));
+
+        assert!(rename_selected_variable(" ", &mut context).is_err());
+        rename_selected_variable("c", &mut context).unwrap();
+        assert_eq!(context.selected_variable_name().as_deref(), Some("c"));
+        context.undo();
+        assert_eq!(context.variables.get("a"), Some(&StackItem::Number(3.0)));
+        context.undo();
+        assert_eq!(context.variables.get("a"), Some(&StackItem::Number(1.0)));
+    }
+}
@@ -1,12 +1,13 @@
 use std::io::Stdout;
 use crossterm::execute;
-use crossterm::style::{Print};
+use crossterm::style::{Color, Print};
 use crossterm::cursor::MoveTo;
 use crossterm::terminal::{Clear, ClearType};
 
 use crate::data::context::AppContext;
 use crate::data::context::AppMode;
-use crate::ui::text_formatting::{print_formatted_at, format_stack_item, TextFormat};
+use crate::data::status::MessageKind;
+use crate::ui::text_formatting::{print_formatted_at, format_number, format_stack_item, TextFormat};
 use crate::stack::item::StackItem;
 
 pub(crate) trait Drawable {
@@ -51,6 +52,29 @@ impl Drawable for InputAreaUpdater {
     }
 }
 
+pub(crate) struct StatusLineDrawer;
+
+impl Drawable for StatusLineDrawer {
+    fn draw(stdout: &mut Stdout, context: &AppContext) {
+        // the row between the main area and the mode text
+        let status_row = context.terminal_size.rows - 4;
+        let max_length = context.terminal_size.cols as usize - 4;
+
+        if let Some(status) = &context.status {
+            let formats = match status.kind {
+                MessageKind::Error => [TextFormat::Bold, TextFormat::Colored(Color::Red)],
+                MessageKind::Warning => [TextFormat::Bold, TextFormat::Colored(Color::Yellow)],
+                MessageKind::Info => [TextFormat::Italic, TextFormat::Colored(Color::Cyan)],
+            };
+            let mut line = format!("{}: {}", status.kind.label(), status.text);
+            if line.chars().count() > max_length {
+                line = format!("{}...", line.chars().take(max_length - 3).collect::<String>());
+            }
+            print_formatted_at(stdout, &line, &formats, 2, status_row);
+        }
+    }
+}
+
 pub(crate) struct MainAreaUpdater;
 
 impl Drawable for MainAreaUpdater {
@@ -63,12 +87,22 @@ impl Drawable for MainAreaUpdater {
         };
 
         print_formatted_at(stdout, mode_text, &[TextFormat::Bold], 1, context.terminal_size.rows - 3);
+        // global settings that change results, right-aligned on the same row
+        let indicators = format!("{}  {}", context.display.format.label(), context.angle.label());
+        let indicator_col = context.terminal_size.cols.saturating_sub(indicators.len() as u16 + 2);
+        print_formatted_at(stdout, &indicators, &[TextFormat::Bold], indicator_col, context.terminal_size.rows - 3);
+
+        // an open list covers whatever the mode would show
+        if context.list_view.is_some() {
+            ListDisplay::draw(stdout, context);
+            return;
+        }
 
         match context.current_mode {
             AppMode::Stack => StackDisplay::draw(stdout, context),
             AppMode::Program => {},  // Implement as needed
-            AppMode::Matrix => {},   // Implement as needed
-            AppMode::Variables => {}, // Implement as needed
+            AppMode::Matrix => MatrixDisplay::draw(stdout, context),
+            AppMode::Variables => VariablesDisplay::draw(stdout, context),
         }
     }
 }
@@ -88,20 +122,20 @@ impl Drawable for StackDisplay {
             let display_index = context.stack.len() - 1 - stack_index; // Correct the index order
 
             match item {
-                StackItem::Number(num) => {
-                    // For Number, display it on a single line
+                StackItem::Array(arr) if !fits_on_line(item, context) => {
+                    // For Array, display its dimensions
                     if display_row > stack_display_start {
-                        let line = format!("{:2}: {:.2}", display_index, num);
+                        let array_type = if arr.len() == 1 { "1D" } else { "2D" };
+                        let dimensions = if arr.len() == 1 { format!("{} elements", arr[0].len()) } else { format!("{}x{} elements", arr.len(), arr[0].len()) };
+                        let line = format!("{:2}: {} Array [{}]", display_index, array_type, dimensions);
                         execute!(stdout, MoveTo(2, display_row), Print(line)).unwrap();
                         display_row -= 1;
                     }
                 },
-                StackItem::Array(arr) => {
-                    // For Array, display its dimensions
+                _ => {
+                    // Everything else fits on a single line
                     if display_row > stack_display_start {
-                        let array_type = if arr.len() == 1 { "1D" } else { "2D" };
-                        let dimensions = if arr.len() == 1 { format!("{} elements", arr[0].len()) } else { format!("{}x{} elements", arr.len(), arr[0].len()) };
-                        let line = format!("{:2}: {} Array [{}]", display_index, array_type, dimensions);
+                        let line = format!("{:2}: {}", display_index, format_stack_item(item, &context.display));
                         execute!(stdout, MoveTo(2, display_row), Print(line)).unwrap();
                         display_row -= 1;
                     }
@@ -114,8 +148,130 @@ impl Drawable for StackDisplay {
 
         // Fill remaining lines with '~'
         while display_row > stack_display_start {
-            execute!(stdout, MoveTo(2, display_row), Print(format!(" ~"))).unwrap();
+            execute!(stdout, MoveTo(2, display_row), Print(" ~")).unwrap();
             display_row -= 1;
         }
     }
 }
+
+// Small arrays are shown in full when the whole row fits in the stack area
+fn fits_on_line(item: &StackItem, context: &AppContext) -> bool {
+    let max_length = context.terminal_size.cols as usize - 8;
+    format_stack_item(item, &context.display).chars().count() <= max_length
+}
+
+pub(crate) struct VariablesDisplay;
+
+impl Drawable for VariablesDisplay {
+    fn draw(stdout: &mut Stdout, context: &AppContext) {
+        let first_row = 1;
+        let last_row = context.terminal_size.rows - 3 - 2; // Same area as the stack display
+        let max_length = context.terminal_size.cols as usize - 4;
+
+        let title = format!(" variables ({}) - enter push, del purge, ctrl-s store level 1 [as typed name], ctrl-r rename to typed name", context.variables.len());
+        let title: String = title.chars().take(max_length).collect();
+        print_formatted_at(stdout, &title, &[TextFormat::Underlined], 2, first_row);
+
+        if context.variables.is_empty() {
+            execute!(stdout, MoveTo(2, first_row + 1), Print(" ~ store a value with .sto name")).unwrap();
+            return;
+        }
+
+        // scroll just far enough to keep the selection in view
+        let visible_rows = last_row.saturating_sub(first_row) as usize;
+        let first_index = (context.selected_variable + 1).saturating_sub(visible_rows);
+        let name_width = context.variables.keys().map(|name| name.chars().count()).max().unwrap_or(0).max(4);
+
+        for (offset, (name, item)) in context.variables.iter().skip(first_index).take(visible_rows).enumerate() {
+            let selected = first_index + offset == context.selected_variable;
+            let marker = if selected { "›" } else { " " };
+            let preview = format_stack_item(item, &context.display);
+            let line = format!("{} {:<name_width$}  {:<8}  {}", marker, name, item.kind().name(), preview, name_width = name_width);
+            let line: String = line.chars().take(max_length).collect();
+            let formats: &[TextFormat] = if selected { &[TextFormat::Bold, TextFormat::Colored(Color::Cyan)] } else { &[] };
+            print_formatted_at(stdout, &line, formats, 2, first_row + 1 + offset as u16);
+        }
+    }
+}
+
+pub(crate) struct MatrixDisplay;
+
+impl Drawable for MatrixDisplay {
+    fn draw(stdout: &mut Stdout, context: &AppContext) {
+        let first_row = 1;
+        let last_row = context.terminal_size.rows - 3 - 2; // Same area as the stack display
+        let max_length = context.terminal_size.cols as usize - 4;
+
+        let editor = match &context.matrix_editor {
+            Some(editor) => editor,
+            None => {
+                print_formatted_at(stdout, " matrix", &[TextFormat::Underlined], 2, first_row);
+                execute!(stdout, MoveTo(2, first_row + 1), Print(" ~ open an array with .edit [level] or start one with .new m n")).unwrap();
+                return;
+            },
+        };
+        let (rows, cols) = editor.shape();
+        let title = format!(
+            " matrix {}x{} ({}, {}) - enter sets cell, shift+arrows select, ctrl-z/y undo/redo, ctrl-s write back, ctrl-p push, esc close",
+            rows, cols, editor.row + 1, editor.col + 1
+        );
+        let title: String = title.chars().take(max_length).collect();
+        print_formatted_at(stdout, &title, &[TextFormat::Underlined], 2, first_row);
+
+        // every column gets the width of the widest cell
+        let cells: Vec<Vec<String>> = editor.data.iter()
+            .map(|row| row.iter().map(|x| format_number(*x, context.display.format)).collect())
+            .collect();
+        let width = cells.iter().flatten().map(|cell| cell.chars().count()).max().unwrap_or(0) + 2;
+        let label_width = rows.to_string().len() + 2;
+
+        // scroll both ways just far enough to keep the cursor in view
+        let visible_rows = last_row.saturating_sub(first_row) as usize;
+        let visible_cols = (max_length.saturating_sub(label_width) / width).max(1);
+        let first_index = (editor.row + 1).saturating_sub(visible_rows);
+        let first_col = (editor.col + 1).saturating_sub(visible_cols);
+        let selection = editor.selection();
+
+        for (offset, row) in cells.iter().skip(first_index).take(visible_rows).enumerate() {
+            let i = first_index + offset;
+            let y = first_row + 1 + offset as u16;
+            print_formatted_at(stdout, &format!("{:>w$}:", i + 1, w = label_width - 1), &[TextFormat::Bold], 2, y);
+            for (col_offset, cell) in row.iter().skip(first_col).take(visible_cols).enumerate() {
+                let j = first_col + col_offset;
+                let x = 2 + label_width + col_offset * width;
+                let text = format!("{:>width$}", cell, width = width);
+                let formats: &[TextFormat] = if (i, j) == (editor.row, editor.col) {
+                    &[TextFormat::Bold, TextFormat::Colored(Color::Cyan), TextFormat::Underlined]
+                } else if editor.anchor.is_some() && selection.contains(i, j) {
+                    &[TextFormat::Colored(Color::Yellow)]
+                } else {
+                    &[]
+                };
+                print_formatted_at(stdout, &text, formats, x as u16, y);
+            }
+        }
+    }
+}
+
+pub(crate) struct ListDisplay;
+
+impl Drawable for ListDisplay {
+    fn draw(stdout: &mut Stdout, context: &AppContext) {
+        let list_view = match &context.list_view {
+            Some(list_view) => list_view,
+            None => return,
+        };
+        let first_row = 1;
+        let last_row = context.terminal_size.rows - 3 - 2; // Same area as the stack display
+        let max_length = context.terminal_size.cols as usize - 4;
+
+        let title = format!(" {} ({}/{}) - esc to close", list_view.title, list_view.scroll + 1, list_view.lines.len());
+        print_formatted_at(stdout, &title, &[TextFormat::Underlined], 2, first_row);
+
+        let visible_rows = last_row.saturating_sub(first_row) as usize;
+        for (offset, line) in list_view.lines.iter().skip(list_view.scroll).take(visible_rows).enumerate() {
+            let line: String = line.chars().take(max_length).collect();
+            execute!(stdout, MoveTo(2, first_row + 1 + offset as u16), Print(line)).unwrap();
+        }
+    }
+}
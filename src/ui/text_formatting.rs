@@ -1,13 +1,17 @@
 use std::io::Stdout;
 use crossterm::execute;
-use crossterm::style::{Attribute, SetAttribute, Print};
+use crossterm::style::{Attribute, Color, SetAttribute, SetForegroundColor, Print};
 use crossterm::cursor::MoveTo;
+use crate::control::parsing::format_string_literal;
+use crate::data::settings::{DisplaySettings, NumberFormat};
+use crate::math::complex::Complex;
 use crate::stack::item::StackItem;
 
 pub(crate) enum TextFormat {
     Bold,
     Italic,
     Underlined,
+    Colored(Color),
 }
 
 pub(crate) fn print_formatted_at(stdout: &mut Stdout, text: &str, formats: &[TextFormat], x: u16, y: u16) {
@@ -17,30 +21,133 @@ pub(crate) fn print_formatted_at(stdout: &mut Stdout, text: &str, formats: &[Tex
             TextFormat::Bold => execute!(stdout, SetAttribute(Attribute::Bold)).unwrap(),
             TextFormat::Italic => execute!(stdout, SetAttribute(Attribute::Italic)).unwrap(),
             TextFormat::Underlined => execute!(stdout, SetAttribute(Attribute::Underlined)).unwrap(),
+            TextFormat::Colored(color) => execute!(stdout, SetForegroundColor(*color)).unwrap(),
         }
     }
     execute!(stdout, Print(text)).unwrap();
-    execute!(stdout, SetAttribute(Attribute::Reset)).unwrap();
+    execute!(stdout, SetAttribute(Attribute::Reset), SetForegroundColor(Color::Reset)).unwrap();
 }
 
 // Helper function to format a StackItem for display
-pub(crate) fn format_stack_item(item: &StackItem) -> String {
+pub(crate) fn format_stack_item(item: &StackItem, display: &DisplaySettings) -> String {
     match item {
-        StackItem::Number(num) => format!("{:.2}", num),
+        StackItem::Number(num) => format_number(*num, display.format),
         StackItem::Array(arr) => {
             if arr.len() == 1 {
                 // Handle as a 1D array
-                format!("[{}]", arr[0].iter().map(|n| format!("{:.2}", n)).collect::<Vec<String>>().join(", "))
+                format!("[{}]", arr[0].iter().map(|n| format_number(*n, display.format)).collect::<Vec<String>>().join(", "))
             } else {
                 // Handle as a 2D array
                 let formatted_rows: Vec<String> = arr.iter().map(|row| {
-                    row.iter().map(|n| format!("{:.2}", n)).collect::<Vec<String>>().join(", ")
+                    row.iter().map(|n| format_number(*n, display.format)).collect::<Vec<String>>().join(", ")
                 }).collect();
                 formatted_rows.join("; ")
             }
         },
+        StackItem::Complex(c) => format_complex(c, display.format),
+        StackItem::Integer(int) => int.to_string(),
+        StackItem::Rational(rational) => rational.to_string(),
+        StackItem::Word(word) => word.format(display.base),
+        StackItem::Quantity(quantity) => format!("{}_{}", format_number(quantity.value, display.format), quantity.unit),
+        StackItem::String(text) => format_string_literal(text),
+        StackItem::Date(date) => date.to_string(),
+        StackItem::Duration(duration) => duration.to_string(),
     }
 }
 
+pub(crate) fn format_complex(c: &Complex, format: NumberFormat) -> String {
+    let sign = if c.im.is_sign_negative() { '-' } else { '+' };
+    format!("{}{}{}i", format_number(c.re, format), sign, format_number(c.im.abs(), format))
+}
+
+// Every float on screen goes through here so the display mode applies everywhere
+pub(crate) fn format_number(num: f64, format: NumberFormat) -> String {
+    if !num.is_finite() {
+        return num.to_string();
+    }
+    let magnitude = num.abs();
+    match format {
+        // plain decimals while they stay short, otherwise scientific
+        NumberFormat::Std if magnitude == 0.0 || (1e-4..1e15).contains(&magnitude) => num.to_string(),
+        NumberFormat::Std => format!("{:e}", num),
+        // numbers too large for a fixed layout fall back to scientific
+        NumberFormat::Fix(digits) if magnitude >= 1e15 => format!("{:.*e}", digits, num),
+        NumberFormat::Fix(digits) => format!("{:.*}", digits, num),
+        NumberFormat::Sci(digits) => format!("{:.*e}", digits, num),
+        NumberFormat::Eng(digits) => format_engineering(num, digits),
+    }
+}
 
+fn format_engineering(num: f64, digits: usize) -> String {
+    // round in scientific form first so 999.96 becomes 1.000e3, not 1000.0e0
+    let scientific = format!("{:.*e}", digits, num);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let (mantissa, exponent) = (mantissa.parse::<f64>().unwrap(), exponent.parse::<i32>().unwrap());
+    let shift = exponent.rem_euclid(3);
+    let decimals = digits.saturating_sub(shift as usize);
+    format!("{:.*}e{}", decimals, mantissa * 10f64.powi(shift), exponent - shift)
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn std_is_the_shortest_text_that_reads_back() {
+        assert_eq!(format_number(0.001, NumberFormat::Std), "0.001");
+        assert_eq!(format_number(0.1 + 0.2, NumberFormat::Std), "0.30000000000000004");
+        assert_eq!(format_number(-2.5, NumberFormat::Std), "-2.5");
+        assert_eq!(format_number(0.0, NumberFormat::Std), "0");
+        assert_eq!(format_number(1e20, NumberFormat::Std), "1e20");
+        assert_eq!(format_number(1.5e-7, NumberFormat::Std), "1.5e-7");
+        for num in [1e20, 1.5e-7, 123456.789, -1e300, 5e-324] {
+            assert_eq!(format_number(num, NumberFormat::Std).parse::<f64>(), Ok(num));
+        }
+    }
+
+    #[test]
+    fn fix_rounds_to_places() {
+        assert_eq!(format_number(0.001, NumberFormat::Fix(2)), "0.00");
+        assert_eq!(format_number(0.001, NumberFormat::Fix(4)), "0.0010");
+        assert_eq!(format_number(-1.23456, NumberFormat::Fix(3)), "-1.235");
+        assert_eq!(format_number(7.0, NumberFormat::Fix(0)), "7");
+    }
+
+    #[test]
+    fn fix_falls_back_to_scientific_for_large_numbers() {
+        // 1e20 in a fixed layout would be 21 digits wide and overflow the line
+        assert_eq!(format_number(1e20, NumberFormat::Fix(2)), "1.00e20");
+        assert_eq!(format_number(-1e300, NumberFormat::Fix(2)), "-1.00e300");
+        assert_eq!(format_number(1e14, NumberFormat::Fix(1)), "100000000000000.0");
+        assert!(format_number(f64::MAX, NumberFormat::Fix(17)).len() < 30);
+    }
+
+    #[test]
+    fn sci_and_eng() {
+        assert_eq!(format_number(12345.6, NumberFormat::Sci(2)), "1.23e4");
+        assert_eq!(format_number(0.000321, NumberFormat::Sci(1)), "3.2e-4");
+        assert_eq!(format_number(12345.6, NumberFormat::Eng(3)), "12.35e3");
+        assert_eq!(format_number(0.000321, NumberFormat::Eng(2)), "321e-6");
+        assert_eq!(format_number(-4.7e-9, NumberFormat::Eng(3)), "-4.700e-9");
+        // rounding can carry into the next group of three
+        assert_eq!(format_number(999.96, NumberFormat::Eng(3)), "1.000e3");
+        assert_eq!(format_number(1e20, NumberFormat::Eng(2)), "100e18");
+    }
+
+    #[test]
+    fn non_finite_values_in_every_mode() {
+        for format in [NumberFormat::Std, NumberFormat::Fix(2), NumberFormat::Sci(2), NumberFormat::Eng(2)] {
+            assert_eq!(format_number(f64::INFINITY, format), "inf");
+            assert_eq!(format_number(f64::NEG_INFINITY, format), "-inf");
+            assert_eq!(format_number(f64::NAN, format), "NaN");
+        }
+    }
+
+    #[test]
+    fn arrays_and_complex_numbers_use_the_mode() {
+        let display = DisplaySettings { format: NumberFormat::Fix(1), ..DisplaySettings::default() };
+        let array = StackItem::Array(vec![vec![1.0, 2.25], vec![1e20, 0.0]]);
+        assert_eq!(format_stack_item(&array, &display), "1.0, 2.2; 1.0e20, 0.0");
+        assert_eq!(format_complex(&Complex::new(1.0, -0.5), NumberFormat::Fix(2)), "1.00-0.50i");
+    }
+}
@@ -0,0 +1,299 @@
+// A small JSON value with a parser and a compact writer, enough to exchange stack
+// items with other tools. Numbers keep their source text so nothing is lost to
+// rounding before the caller decides how to read them.
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    // fields in written order
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    // JSON has no infinities or NaN, so those are written as the strings Rust reads back
+    pub(crate) fn float(value: f64) -> Json {
+        if value.is_finite() {
+            Json::Number(format!("{:?}", value))
+        } else {
+            Json::String(format!("{:?}", value))
+        }
+    }
+
+    pub(crate) fn text(value: impl Into<String>) -> Json {
+        Json::String(value.into())
+    }
+
+    pub(crate) fn object(fields: Vec<(&str, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Result<&Json, String> {
+        match self {
+            Json::Object(fields) => fields.iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value)
+                .ok_or_else(|| format!("Missing JSON field: {}", key)),
+            _ => Err("Expected a JSON object".to_string()),
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Result<&str, String> {
+        match self {
+            Json::String(text) => Ok(text),
+            _ => Err("Expected a JSON string".to_string()),
+        }
+    }
+
+    pub(crate) fn as_bool(&self) -> Result<bool, String> {
+        match self {
+            Json::Bool(value) => Ok(*value),
+            _ => Err("Expected a JSON boolean".to_string()),
+        }
+    }
+
+    // Numbers, plus the "inf" / "-inf" / "NaN" strings written by Json::float
+    pub(crate) fn as_f64(&self) -> Result<f64, String> {
+        match self {
+            Json::Number(text) => text.parse::<f64>().map_err(|_| format!("Invalid JSON number: {}", text)),
+            Json::String(text) if matches!(text.as_str(), "inf" | "-inf" | "NaN") => Ok(text.parse::<f64>().unwrap()),
+            _ => Err("Expected a JSON number".to_string()),
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Result<&[Json], String> {
+        match self {
+            Json::Array(items) => Ok(items),
+            _ => Err("Expected a JSON array".to_string()),
+        }
+    }
+
+    pub(crate) fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = Parser { chars: text.chars().collect(), position: 0, depth: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position < parser.chars.len() {
+            return Err(format!("Unexpected text after JSON value at {}", parser.position));
+        }
+        Ok(value)
+    }
+}
+
+impl std::fmt::Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(text) => write!(f, "{}", text),
+            Json::String(text) => write_string(f, text),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+fn write_string(f: &mut std::fmt::Formatter<'_>, text: &str) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for ch in text.chars() {
+        match ch {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            ch if (ch as u32) < 0x20 => write!(f, "\\u{:04x}", ch as u32)?,
+            other => write!(f, "{}", other)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+// Arrays and objects nested deeper than this are refused rather than risking the
+// stack in the recursive parser; items never need more than a few levels
+const MAX_DEPTH: usize = 64;
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    // arrays and objects currently open
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek();
+        self.position += 1;
+        ch
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|ch| ch.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.next() {
+            Some(ch) if ch == expected => Ok(()),
+            Some(ch) => Err(format!("Expected '{}' in JSON, found '{}'", expected, ch)),
+            None => Err(format!("Expected '{}' at the end of the JSON", expected)),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Json) -> Result<Json, String> {
+        for expected in word.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        if matches!(self.peek(), Some('[' | '{')) {
+            if self.depth == MAX_DEPTH {
+                return Err(format!("JSON nested more than {} levels deep", MAX_DEPTH));
+            }
+            self.depth += 1;
+            let value = if self.peek() == Some('[') { self.array() } else { self.object() };
+            self.depth -= 1;
+            return value;
+        }
+        match self.peek() {
+            Some('n') => self.keyword("null", Json::Null),
+            Some('t') => self.keyword("true", Json::Bool(true)),
+            Some('f') => self.keyword("false", Json::Bool(false)),
+            Some('"') => self.string().map(Json::String),
+            Some(ch) if ch == '-' || ch.is_ascii_digit() => self.number(),
+            Some(ch) => Err(format!("Unexpected '{}' in JSON", ch)),
+            None => Err("Unexpected end of JSON".to_string()),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.position;
+        while self.peek().is_some_and(|ch| ch.is_ascii_digit() || matches!(ch, '-' | '+' | '.' | 'e' | 'E')) {
+            self.position += 1;
+        }
+        let text: String = self.chars[start..self.position].iter().collect();
+        match text.parse::<f64>() {
+            Ok(_) => Ok(Json::Number(text)),
+            Err(_) => Err(format!("Invalid JSON number: {}", text)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(text),
+                Some('\\') => match self.next() {
+                    Some('"') => text.push('"'),
+                    Some('\\') => text.push('\\'),
+                    Some('/') => text.push('/'),
+                    Some('b') => text.push('\u{8}'),
+                    Some('f') => text.push('\u{c}'),
+                    Some('n') => text.push('\n'),
+                    Some('r') => text.push('\r'),
+                    Some('t') => text.push('\t'),
+                    Some('u') => text.push(self.unicode_escape()?),
+                    Some(other) => return Err(format!("Unknown JSON escape: \\{}", other)),
+                    None => break,
+                },
+                Some(other) => text.push(other),
+                None => break,
+            }
+        }
+        Err("Unterminated JSON string".to_string())
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits: String = (0..4).filter_map(|_| self.next()).collect();
+        u32::from_str_radix(&digits, 16).map_err(|_| format!("Invalid JSON escape: \\u{}", digits))
+    }
+
+    // \uXXXX, joining UTF-16 surrogate pairs
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            self.expect('\\')?;
+            self.expect('u')?;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err("Invalid JSON surrogate pair".to_string());
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| "Invalid JSON character escape".to_string())
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(items)),
+                _ => return Err("Expected ',' or ']' in JSON array".to_string()),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(Json::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(fields)),
+                _ => return Err("Expected ',' or '}' in JSON object".to_string()),
+            }
+        }
+    }
+}
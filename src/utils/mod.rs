@@ -1,2 +1,2 @@
 pub(crate) mod misc;
-pub(crate) mod terminal;
\ No newline at end of file
+pub(crate) mod terminal;pub(crate) mod json;
@@ -6,8 +6,9 @@ pub(crate) struct TerminalSize {
 }
 
 impl TerminalSize {
+    // Falls back to 80x24 when there is no terminal to ask, as under cargo test
     pub fn new() -> TerminalSize {
-        let (cols, rows) = size().unwrap();
+        let (cols, rows) = size().unwrap_or((80, 24));
         TerminalSize { cols, rows }
     }
 